@@ -13,6 +13,7 @@ const MAILBOX_CAPACITY: usize = 100;
 
 /// Simulated Actor with mailbox
 struct Actor {
+    #[allow(dead_code)]
     id: usize,
     mailbox: VecDeque<Message>,
     processed: usize,
@@ -147,10 +148,8 @@ fn benchmark_actor_hotspot(requests_per_tick: usize, ticks: usize) -> ActorHotsp
     for tick in 0..ticks {
         // Send requests to the single actor
         for _ in 0..requests_per_tick {
-            if !system.send_to(0, msg.clone()) {
-                if first_rejection_tick.is_none() {
-                    first_rejection_tick = Some(tick);
-                }
+            if !system.send_to(0, msg.clone()) && first_rejection_tick.is_none() {
+                first_rejection_tick = Some(tick);
             }
         }
         // Actor processes messages
@@ -231,9 +230,9 @@ fn main() {
         let actors = 100; // 100 actors to receive messages
         let result = benchmark_actor_load_spike(requests, actors);
         println!(
-            "  {:>8} requests → {} actors: {:>6} accepted, {:>6} rejected ({:>5.1}%), memory: {} KB",
-            requests, actors, result.accepted, result.rejected, result.rejection_rate,
-            result.memory_bytes / 1024
+            "  {:>8} requests → {} actors: {:>6} accepted, {:>6} rejected ({:>5.1}%), memory: {} KB, {:?}",
+            result.requests, result.actors, result.accepted, result.rejected, result.rejection_rate,
+            result.memory_bytes / 1024, result.duration
         );
     }
 
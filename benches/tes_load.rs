@@ -5,10 +5,13 @@
 //! - Rejection latency: sub-microsecond
 //! - Recovery: automatic via decay
 //!
+//! Each scenario is written once against `TraceField` and run for every
+//! grid layout.
+//!
 //! Run with: cargo bench --bench tes_load
 
 use std::time::{Duration, Instant};
-use tes::{IsotopeGrid, ServiceColor};
+use tes::{DensityGrid, IsotopeGrid, ServiceColor, TraceField};
 
 // Benchmark configuration - matches viz.rs physics
 const GRID_WIDTH: usize = 256;
@@ -17,17 +20,31 @@ const DECAY_RATE: u32 = 5; // Balanced for recovery
 const HABITABILITY_THRESHOLD: u32 = 2500; // Earlier choking
 const INTENSITY: u32 = 200;
 
-/// Scenario 1: Load Spike
-/// 10,000 requests hit the system simultaneously
-fn benchmark_load_spike(requests: usize) -> LoadSpikeResult {
-    let grid = IsotopeGrid::new(
+/// Monochrome layout
+fn density_grid() -> DensityGrid {
+    DensityGrid::new(
         GRID_WIDTH,
         GRID_HEIGHT,
         DECAY_RATE,
         HABITABILITY_THRESHOLD,
         HABITABILITY_THRESHOLD / 2,
-    );
+    )
+}
+
+/// RGB isotope layout
+fn isotope_grid() -> IsotopeGrid {
+    IsotopeGrid::new(
+        GRID_WIDTH,
+        GRID_HEIGHT,
+        DECAY_RATE,
+        HABITABILITY_THRESHOLD,
+        HABITABILITY_THRESHOLD / 2,
+    )
+}
 
+/// Scenario 1: Load Spike
+/// 10,000 requests hit the system simultaneously
+fn benchmark_load_spike<F: TraceField>(grid: F, requests: usize) -> LoadSpikeResult {
     let color = ServiceColor::from_name("TestService");
     let mut accepted = 0usize;
     let mut rejected = 0usize;
@@ -41,7 +58,7 @@ fn benchmark_load_spike(requests: usize) -> LoadSpikeResult {
 
         if x < GRID_WIDTH && y < GRID_HEIGHT {
             if grid.is_habitable(x, y, HABITABILITY_THRESHOLD) {
-                grid.contribute(x, y, INTENSITY, color);
+                grid.contribute_colored(x, y, INTENSITY, color);
                 accepted += 1;
             } else {
                 rejected += 1;
@@ -63,15 +80,7 @@ fn benchmark_load_spike(requests: usize) -> LoadSpikeResult {
 
 /// Scenario 2: Hotspot Saturation
 /// One service receives continuous traffic until saturation
-fn benchmark_hotspot_saturation() -> SaturationResult {
-    let grid = IsotopeGrid::new(
-        GRID_WIDTH,
-        GRID_HEIGHT,
-        DECAY_RATE,
-        HABITABILITY_THRESHOLD,
-        HABITABILITY_THRESHOLD / 2,
-    );
-
+fn benchmark_hotspot_saturation<F: TraceField>(grid: F) -> SaturationResult {
     let color = ServiceColor::from_name("HotspotService");
     let center_x = GRID_WIDTH / 2;
     let center_y = GRID_HEIGHT / 2;
@@ -87,7 +96,7 @@ fn benchmark_hotspot_saturation() -> SaturationResult {
         // 100 contributions per tick
         for _ in 0..100 {
             if grid.is_habitable(center_x, center_y, HABITABILITY_THRESHOLD) {
-                grid.contribute(center_x, center_y, INTENSITY, color);
+                grid.contribute_colored(center_x, center_y, INTENSITY, color);
             } else {
                 if !found_first_rejection {
                     ticks_to_first_rejection = tick;
@@ -111,22 +120,14 @@ fn benchmark_hotspot_saturation() -> SaturationResult {
 
 /// Scenario 3: Cold Start Recovery
 /// After saturation, measure time to clear via decay
-fn benchmark_cold_start_recovery() -> RecoveryResult {
-    let grid = IsotopeGrid::new(
-        GRID_WIDTH,
-        GRID_HEIGHT,
-        DECAY_RATE,
-        HABITABILITY_THRESHOLD,
-        HABITABILITY_THRESHOLD / 2,
-    );
-
+fn benchmark_cold_start_recovery<F: TraceField>(grid: F) -> RecoveryResult {
     let color = ServiceColor::from_name("RecoveryService");
     let center_x = GRID_WIDTH / 2;
     let center_y = GRID_HEIGHT / 2;
 
     // Saturate the center
     for _ in 0..100 {
-        grid.contribute(center_x, center_y, INTENSITY, color);
+        grid.contribute_colored(center_x, center_y, INTENSITY, color);
     }
 
     let initial_density = grid.density(center_x, center_y);
@@ -181,22 +182,39 @@ struct RecoveryResult {
 fn main() {
     println!("=== TES Load Benchmark ===\n");
 
+    run_scenarios("DensityGrid", density_grid);
+    run_scenarios("IsotopeGrid", isotope_grid);
+
+    println!("\n=== TES Key Metrics ===");
+    println!(
+        "  Memory: O(grid_size) = {} bytes (DensityGrid), {} bytes (IsotopeGrid) constant",
+        GRID_WIDTH * GRID_HEIGHT * 5,
+        GRID_WIDTH * GRID_HEIGHT * 17
+    );
+    println!("  Rejection: Physical barrier (not rate limiting)");
+    println!("  Recovery: Automatic via decay");
+}
+
+fn run_scenarios<F: TraceField>(layout: &str, make_grid: fn() -> F) {
+    println!("##### {} #####\n", layout);
+
     // Scenario 1: Load Spike
     println!("--- Scenario 1: Load Spike ---");
     for requests in [1_000, 10_000, 100_000, 1_000_000] {
-        let result = benchmark_load_spike(requests);
+        let result = benchmark_load_spike(make_grid(), requests);
         println!(
-            "  {:>8} requests: {:>6} accepted, {:>6} rejected ({:>5.1}%), avg latency: {:.0}ns",
-            requests,
+            "  {:>8} requests: {:>6} accepted, {:>6} rejected ({:>5.1}%), avg latency: {:.0}ns, {:?}",
+            result.requests,
             result.accepted,
             result.rejected,
             result.rejection_rate,
-            result.avg_latency_ns
+            result.avg_latency_ns,
+            result.duration
         );
     }
 
     println!("\n--- Scenario 2: Hotspot Saturation ---");
-    let sat_result = benchmark_hotspot_saturation();
+    let sat_result = benchmark_hotspot_saturation(make_grid());
     println!(
         "  First rejection at tick: {}\n  Total rejected: {}\n  Final density: {}\n  Duration: {:?}",
         sat_result.ticks_to_first_rejection,
@@ -206,20 +224,12 @@ fn main() {
     );
 
     println!("\n--- Scenario 3: Cold Start Recovery ---");
-    let rec_result = benchmark_cold_start_recovery();
+    let rec_result = benchmark_cold_start_recovery(make_grid());
     println!(
-        "  Initial density: {}\n  Ticks to recover: {}\n  Final density: {}\n  Duration: {:?}",
+        "  Initial density: {}\n  Ticks to recover: {}\n  Final density: {}\n  Duration: {:?}\n",
         rec_result.initial_density,
         rec_result.ticks_to_recover,
         rec_result.final_density,
         rec_result.duration
     );
-
-    println!("\n=== TES Key Metrics ===");
-    println!(
-        "  Memory: O(grid_size) = {} bytes constant",
-        GRID_WIDTH * GRID_HEIGHT * 12
-    );
-    println!("  Rejection: Physical barrier (not rate limiting)");
-    println!("  Recovery: Automatic via decay");
 }
@@ -0,0 +1,38 @@
+//! Build script: with the `capi` feature, generate the C header and compile
+//! the C test program linked by the `capi` tests.
+
+fn main() {
+    #[cfg(feature = "capi")]
+    capi();
+}
+
+#[cfg(feature = "capi")]
+fn capi() {
+    use std::env;
+    use std::path::PathBuf;
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("C header generation failed")
+        .write_to_file(out_dir.join("tes.h"));
+
+    // Compiled against the generated header; linked only by the unit tests
+    cc::Build::new()
+        .file("capi/test.c")
+        .include(&out_dir)
+        .warnings_into_errors(true)
+        .cargo_metadata(false)
+        .compile("tes_capi_test");
+    println!("cargo:rustc-link-search=native={}", out_dir.display());
+
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=capi/test.c");
+}
@@ -0,0 +1,305 @@
+/* TES C API - generated by cbindgen from src/capi.rs, do not edit. */
+
+#ifndef TES_H
+#define TES_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ Physical regime of a cell (A5).
+ */
+typedef enum {
+  TES_REGIME_SOLID,
+  TES_REGIME_LIQUID,
+  TES_REGIME_GAS,
+} TesRegime;
+
+/*
+ Result of calls that can fail without returning a handle.
+ */
+typedef enum {
+  TES_STATUS_OK,
+  /*
+   A required pointer was NULL
+   */
+  TES_STATUS_NULL,
+  /*
+   Output buffer is smaller than the length written to `len`
+   */
+  TES_STATUS_BUFFER_TOO_SMALL,
+} TesStatus;
+
+/*
+ Opaque handle to an RGB isotope grid.
+ */
+typedef struct TesGrid TesGrid;
+
+/*
+ Opaque handle to a substrate living on an isotope grid.
+ */
+typedef struct TesSubstrate TesSubstrate;
+
+/*
+ Trace color, one fixed-point weight per channel.
+ */
+typedef struct {
+  uint32_t r;
+  uint32_t g;
+  uint32_t b;
+} TesColor;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Deterministic color for a service name (NUL-terminated).
+
+ Returns the neutral color for NULL.
+
+ # Safety
+
+ `name` must be NULL or a valid NUL-terminated string.
+ */
+TesColor tes_color_from_name(const char *name);
+
+/*
+ Create a grid. Returns NULL if the parameters are rejected by
+ [`IsotopeGrid::try_new`].
+ */
+TesGrid *tes_grid_new(size_t width,
+                      size_t height,
+                      uint32_t decay_rate,
+                      uint32_t solid_threshold,
+                      uint32_t liquid_threshold);
+
+/*
+ Release a grid. NULL is ignored.
+
+ # Safety
+
+ `grid` must be NULL or a handle not yet freed.
+ */
+void tes_grid_free(TesGrid *grid);
+
+/*
+ Width of the grid.
+
+ # Safety
+
+ `grid` must be a valid handle.
+ */
+size_t tes_grid_width(const TesGrid *grid);
+
+/*
+ Height of the grid.
+
+ # Safety
+
+ `grid` must be a valid handle.
+ */
+size_t tes_grid_height(const TesGrid *grid);
+
+/*
+ Leave `amount` of trace in `color` at a cell. Off-grid cells are ignored.
+
+ # Safety
+
+ `grid` must be a valid handle.
+ */
+void tes_grid_contribute(const TesGrid *grid, size_t x, size_t y, uint32_t amount, TesColor color);
+
+/*
+ Contribute only if the cell is below `threshold` (A1). Check and
+ contribution are one atomic step.
+
+ # Safety
+
+ `grid` must be a valid handle.
+ */
+bool tes_grid_try_contribute(const TesGrid *grid,
+                             size_t x,
+                             size_t y,
+                             uint32_t amount,
+                             TesColor color,
+                             uint32_t threshold);
+
+/*
+ Total density at a cell (0 off-grid).
+
+ # Safety
+
+ `grid` must be a valid handle.
+ */
+uint32_t tes_grid_density(const TesGrid *grid, size_t x, size_t y);
+
+/*
+ Regime of a cell (gas off-grid).
+
+ # Safety
+
+ `grid` must be a valid handle.
+ */
+TesRegime tes_grid_regime(const TesGrid *grid, size_t x, size_t y);
+
+/*
+ Per-channel density of a cell.
+
+ # Safety
+
+ `grid` must be a valid handle.
+ */
+TesColor tes_grid_rgb(const TesGrid *grid, size_t x, size_t y);
+
+/*
+ Apply one tick of decay (A3).
+
+ # Safety
+
+ `grid` must be a valid handle with no concurrent calls.
+ */
+void tes_grid_decay(TesGrid *grid);
+
+/*
+ Apply one step of diffusion.
+
+ # Safety
+
+ `grid` must be a valid handle with no concurrent calls.
+ */
+void tes_grid_diffuse(TesGrid *grid);
+
+/*
+ Write a snapshot of the grid into `buf`.
+
+ `len` receives the snapshot size even when `cap` is too small, so the
+ buffer can be sized with a first call passing `buf = NULL, cap = 0`.
+
+ # Safety
+
+ `grid` must be a valid handle, `buf` valid for `cap` bytes and `len`
+ valid for writes.
+ */
+TesStatus tes_grid_snapshot(const TesGrid *grid, uint8_t *buf, size_t cap, size_t *len);
+
+/*
+ Restore a grid from a snapshot. Returns NULL on corrupt input.
+
+ # Safety
+
+ `buf` must be valid for `len` bytes.
+ */
+TesGrid *tes_grid_restore(const uint8_t *buf, size_t len);
+
+/*
+ Create a substrate. Shapes are admitted where density is below
+ `solid_threshold`. Returns NULL on invalid parameters.
+ */
+TesSubstrate *tes_substrate_new(size_t width,
+                                size_t height,
+                                uint32_t decay_rate,
+                                uint32_t solid_threshold,
+                                uint32_t liquid_threshold);
+
+/*
+ Release a substrate. NULL is ignored.
+
+ # Safety
+
+ `substrate` must be NULL or a handle not yet freed.
+ */
+void tes_substrate_free(TesSubstrate *substrate);
+
+/*
+ Spawn a shape contributing in `color`.
+
+ Returns the shape ID, or 0 if the position is not habitable (A1).
+
+ # Safety
+
+ `substrate` must be a valid handle.
+ */
+uint64_t tes_substrate_spawn(TesSubstrate *substrate,
+                             size_t x,
+                             size_t y,
+                             uint32_t lifetime,
+                             uint32_t contribution,
+                             TesColor color);
+
+/*
+ Advance one tick: shapes contribute and age, then the field decays.
+
+ # Safety
+
+ `substrate` must be a valid handle.
+ */
+void tes_substrate_tick(TesSubstrate *substrate);
+
+/*
+ Ticks elapsed.
+
+ # Safety
+
+ `substrate` must be a valid handle.
+ */
+uint64_t tes_substrate_tick_count(const TesSubstrate *substrate);
+
+/*
+ Number of living shapes.
+
+ # Safety
+
+ `substrate` must be a valid handle.
+ */
+size_t tes_substrate_shape_count(const TesSubstrate *substrate);
+
+/*
+ Total density at a cell of the substrate's field.
+
+ # Safety
+
+ `substrate` must be a valid handle.
+ */
+uint32_t tes_substrate_density(const TesSubstrate *substrate, size_t x, size_t y);
+
+/*
+ Regime of a cell of the substrate's field.
+
+ # Safety
+
+ `substrate` must be a valid handle.
+ */
+TesRegime tes_substrate_regime(const TesSubstrate *substrate, size_t x, size_t y);
+
+/*
+ Write a snapshot of the substrate's terrain and tick count (living
+ shapes are not recorded). Buffer handling as in [`tes_grid_snapshot`].
+
+ # Safety
+
+ `substrate` must be a valid handle, `buf` valid for `cap` bytes and
+ `len` valid for writes.
+ */
+TesStatus tes_substrate_snapshot(const TesSubstrate *substrate,
+                                 uint8_t *buf,
+                                 size_t cap,
+                                 size_t *len);
+
+/*
+ Restore a substrate from a snapshot. Returns NULL on corrupt input.
+
+ # Safety
+
+ `buf` must be valid for `len` bytes.
+ */
+TesSubstrate *tes_substrate_restore(const uint8_t *buf, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TES_H */
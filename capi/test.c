@@ -0,0 +1,111 @@
+/* C test program for the TES C API, linked into `cargo test --features capi`. */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "tes.h"
+
+#define CHECK(cond)                                                   \
+    do {                                                              \
+        if (!(cond)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,   \
+                    __LINE__, #cond);                                 \
+            return 1;                                                 \
+        }                                                             \
+    } while (0)
+
+static int grid(void) {
+    TesColor auth = tes_color_from_name("AuthService");
+    TesColor red = {1000, 0, 0};
+    TesGrid *g;
+    TesGrid *copy;
+    TesColor rgb;
+    uint8_t *buf;
+    size_t len = 0;
+
+    CHECK(tes_grid_new(0, 8, 5, 1000, 500) == NULL);
+    CHECK(tes_grid_new(8, 8, 2000, 1000, 500) == NULL);
+
+    g = tes_grid_new(8, 4, 100, 1000, 500);
+    CHECK(g != NULL);
+    CHECK(tes_grid_width(g) == 8 && tes_grid_height(g) == 4);
+
+    /* A1: saturated cells refuse further trace */
+    CHECK(tes_grid_try_contribute(g, 2, 1, 600, red, 1000));
+    CHECK(tes_grid_try_contribute(g, 2, 1, 600, red, 1000));
+    CHECK(!tes_grid_try_contribute(g, 2, 1, 600, red, 1000));
+    CHECK(tes_grid_density(g, 2, 1) == 1200);
+    CHECK(tes_grid_regime(g, 2, 1) == TES_REGIME_SOLID);
+    CHECK(tes_grid_regime(g, 0, 0) == TES_REGIME_GAS);
+    CHECK(tes_grid_density(g, 99, 99) == 0);
+
+    tes_grid_contribute(g, 5, 3, 300, auth);
+    CHECK(tes_grid_density(g, 5, 3) > 0);
+
+    /* A3: decay removes trace */
+    tes_grid_decay(g);
+    CHECK(tes_grid_density(g, 2, 1) == 1100);
+    rgb = tes_grid_rgb(g, 2, 1);
+    CHECK(rgb.r == 1100 && rgb.g == 0 && rgb.b == 0);
+    tes_grid_diffuse(g);
+    CHECK(tes_grid_density(g, 1, 1) > 0);
+
+    /* Snapshot: size query, then copy */
+    CHECK(tes_grid_snapshot(g, NULL, 0, &len) == TES_STATUS_BUFFER_TOO_SMALL);
+    CHECK(len > 0);
+    CHECK(tes_grid_snapshot(g, NULL, 0, NULL) == TES_STATUS_NULL);
+    buf = malloc(len);
+    CHECK(buf != NULL);
+    CHECK(tes_grid_snapshot(g, buf, len, &len) == TES_STATUS_OK);
+    copy = tes_grid_restore(buf, len);
+    CHECK(copy != NULL);
+    CHECK(tes_grid_density(copy, 2, 1) == tes_grid_density(g, 2, 1));
+    CHECK(tes_grid_restore(buf, len / 2) == NULL);
+    free(buf);
+
+    tes_grid_free(copy);
+    tes_grid_free(g);
+    tes_grid_free(NULL);
+    return 0;
+}
+
+static int substrate(void) {
+    TesColor blue = {0, 0, 1000};
+    TesSubstrate *s;
+    TesSubstrate *copy;
+    uint8_t buf[4096];
+    size_t len = 0;
+    uint64_t id;
+
+    CHECK(tes_substrate_new(4, 4, 5, 400, 900) == NULL);
+
+    s = tes_substrate_new(4, 4, 0, 100, 50);
+    CHECK(s != NULL);
+    id = tes_substrate_spawn(s, 1, 1, 3, 60, blue);
+    CHECK(id != 0);
+    CHECK(tes_substrate_shape_count(s) == 1);
+
+    tes_substrate_tick(s);
+    tes_substrate_tick(s);
+    CHECK(tes_substrate_tick_count(s) == 2);
+    CHECK(tes_substrate_density(s, 1, 1) == 120);
+    CHECK(tes_substrate_regime(s, 1, 1) == TES_REGIME_SOLID);
+
+    /* A1: the cell is saturated, no new shape fits */
+    CHECK(tes_substrate_spawn(s, 1, 1, 3, 60, blue) == 0);
+
+    CHECK(tes_substrate_snapshot(s, buf, sizeof buf, &len) == TES_STATUS_OK);
+    copy = tes_substrate_restore(buf, len);
+    CHECK(copy != NULL);
+    CHECK(tes_substrate_tick_count(copy) == 2);
+    CHECK(tes_substrate_density(copy, 1, 1) == 120);
+    CHECK(tes_substrate_shape_count(copy) == 0);
+
+    tes_substrate_free(copy);
+    tes_substrate_free(s);
+    return 0;
+}
+
+int tes_capi_selftest(void) {
+    return grid() || substrate();
+}
@@ -0,0 +1,309 @@
+//! Spawn accounting - acceptance and rejection counters
+//!
+//! Rejection is TES backpressure (A1). `Substrate` records every admission
+//! decision here, so acceptance rates can be reported without wrapping
+//! every spawn call.
+//!
+//! Breakdowns by region and by service color are opt-in: they cost memory
+//! proportional to the number of regions / colors seen.
+
+use std::collections::HashMap;
+
+use crate::isotope::ServiceColor;
+
+/// Accepted and rejected spawn counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpawnCounts {
+    /// Spawns admitted
+    pub accepted: u64,
+    /// Spawns rejected as not habitable
+    pub rejected: u64,
+}
+
+impl SpawnCounts {
+    /// Total spawn attempts.
+    pub fn attempted(&self) -> u64 {
+        self.accepted + self.rejected
+    }
+
+    /// Fraction of attempts rejected (0 if none were attempted).
+    pub fn rejection_rate(&self) -> f32 {
+        match self.attempted() {
+            0 => 0.0,
+            n => self.rejected as f32 / n as f32,
+        }
+    }
+
+    fn record(&mut self, accepted: bool) {
+        if accepted {
+            self.accepted += 1;
+        } else {
+            self.rejected += 1;
+        }
+    }
+}
+
+/// Where spawns were recently rejected.
+///
+/// Each rejection flashes its cell to full intensity (255); every
+/// [`fade`](RejectionHeatmap::fade) dims all cells by `fade_step`.
+/// A step of 255 shows only the rejections of the last tick.
+#[derive(Debug, Clone)]
+pub struct RejectionHeatmap {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+    fade_step: u8,
+}
+
+impl RejectionHeatmap {
+    /// Create an empty heatmap.
+    pub fn new(width: usize, height: usize, fade_step: u8) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![0; width * height],
+            fade_step,
+        }
+    }
+
+    /// Flash a rejection at position. Out-of-bounds positions are ignored.
+    pub fn record(&mut self, x: usize, y: usize) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = u8::MAX;
+        }
+    }
+
+    /// Dim every cell by one step.
+    pub fn fade(&mut self) {
+        for cell in &mut self.cells {
+            *cell = cell.saturating_sub(self.fade_step);
+        }
+    }
+
+    /// Clear all flashes.
+    pub fn clear(&mut self) {
+        self.cells.fill(0);
+    }
+
+    /// Get flash intensity at position (0 = no recent rejection).
+    pub fn intensity(&self, x: usize, y: usize) -> u8 {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x]
+        } else {
+            0
+        }
+    }
+
+    /// Get intensities as a row-major slice.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.cells
+    }
+
+    /// Get dimensions.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+/// Cumulative counts per square tile of the space.
+#[derive(Debug, Clone)]
+struct RegionCounts {
+    size: usize,
+    columns: usize,
+    counts: Vec<SpawnCounts>,
+}
+
+impl RegionCounts {
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        let (column, row) = (x / self.size, y / self.size);
+        let i = row * self.columns + column;
+        (column < self.columns && i < self.counts.len()).then_some(i)
+    }
+}
+
+/// Spawn acceptance statistics of a substrate.
+#[derive(Debug, Clone, Default)]
+pub struct SpawnStats {
+    /// Counts since the current tick started
+    current: SpawnCounts,
+    /// Counts of the last completed tick
+    last_tick: SpawnCounts,
+    /// Counts since creation
+    total: SpawnCounts,
+    regions: Option<RegionCounts>,
+    colors: Option<HashMap<ServiceColor, SpawnCounts>>,
+    heatmap: Option<RejectionHeatmap>,
+}
+
+impl SpawnStats {
+    /// Counts since the current tick started.
+    pub fn current(&self) -> SpawnCounts {
+        self.current
+    }
+
+    /// Counts of the last completed tick.
+    pub fn last_tick(&self) -> SpawnCounts {
+        self.last_tick
+    }
+
+    /// Counts since creation.
+    pub fn total(&self) -> SpawnCounts {
+        self.total
+    }
+
+    /// Cumulative counts of the region containing position.
+    ///
+    /// `None` unless region tracking is enabled.
+    pub fn region_at(&self, x: usize, y: usize) -> Option<SpawnCounts> {
+        let regions = self.regions.as_ref()?;
+        regions.index(x, y).map(|i| regions.counts[i])
+    }
+
+    /// Cumulative counts of a service color.
+    ///
+    /// `None` unless color tracking is enabled.
+    pub fn color(&self, color: ServiceColor) -> Option<SpawnCounts> {
+        let colors = self.colors.as_ref()?;
+        Some(colors.get(&color).copied().unwrap_or_default())
+    }
+
+    /// Iterate over cumulative counts of every color seen.
+    pub fn colors(&self) -> impl Iterator<Item = (ServiceColor, SpawnCounts)> + '_ {
+        self.colors
+            .iter()
+            .flat_map(|colors| colors.iter().map(|(c, n)| (*c, *n)))
+    }
+
+    /// Get the rejection heatmap, if enabled.
+    pub fn heatmap(&self) -> Option<&RejectionHeatmap> {
+        self.heatmap.as_ref()
+    }
+
+    pub(crate) fn track_regions(&mut self, width: usize, height: usize, size: usize) {
+        let size = size.max(1);
+        let columns = width.div_ceil(size);
+        let rows = height.div_ceil(size);
+        self.regions = Some(RegionCounts {
+            size,
+            columns,
+            counts: vec![SpawnCounts::default(); columns * rows],
+        });
+    }
+
+    pub(crate) fn track_colors(&mut self) {
+        self.colors.get_or_insert_with(HashMap::new);
+    }
+
+    pub(crate) fn track_heatmap(&mut self, width: usize, height: usize, fade_step: u8) {
+        self.heatmap = Some(RejectionHeatmap::new(width, height, fade_step));
+    }
+
+    /// Record one admission decision.
+    pub(crate) fn record(&mut self, x: usize, y: usize, color: ServiceColor, accepted: bool) {
+        self.current.record(accepted);
+        self.total.record(accepted);
+
+        if let Some(regions) = &mut self.regions {
+            if let Some(i) = regions.index(x, y) {
+                regions.counts[i].record(accepted);
+            }
+        }
+        if let Some(colors) = &mut self.colors {
+            colors.entry(color).or_default().record(accepted);
+        }
+        if !accepted {
+            if let Some(heatmap) = &mut self.heatmap {
+                heatmap.record(x, y);
+            }
+        }
+    }
+
+    /// Close the current tick.
+    pub(crate) fn end_tick(&mut self) {
+        self.last_tick = std::mem::take(&mut self.current);
+        if let Some(heatmap) = &mut self.heatmap {
+            heatmap.fade();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_and_total_counts() {
+        let mut stats = SpawnStats::default();
+        stats.record(0, 0, ServiceColor::neutral(), true);
+        stats.record(0, 0, ServiceColor::neutral(), false);
+        assert_eq!(stats.current().attempted(), 2);
+
+        stats.end_tick();
+        stats.record(0, 0, ServiceColor::neutral(), false);
+
+        assert_eq!(
+            stats.last_tick(),
+            SpawnCounts {
+                accepted: 1,
+                rejected: 1
+            }
+        );
+        assert_eq!(stats.current().rejected, 1);
+        assert_eq!(stats.total().rejected, 2);
+        assert!((stats.total().rejection_rate() - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_breakdowns_are_opt_in() {
+        let mut stats = SpawnStats::default();
+        stats.record(1, 1, ServiceColor::red(), false);
+        assert_eq!(stats.region_at(1, 1), None);
+        assert_eq!(stats.color(ServiceColor::red()), None);
+        assert!(stats.heatmap().is_none());
+
+        stats.track_regions(10, 10, 4);
+        stats.track_colors();
+        stats.record(1, 1, ServiceColor::red(), false);
+        stats.record(3, 2, ServiceColor::blue(), true);
+        stats.record(9, 9, ServiceColor::red(), true);
+
+        // (1,1) and (3,2) share the first 4x4 tile; (9,9) is in the last
+        assert_eq!(
+            stats.region_at(0, 0),
+            Some(SpawnCounts {
+                accepted: 1,
+                rejected: 1
+            })
+        );
+        assert_eq!(stats.region_at(8, 8).unwrap().accepted, 1);
+        assert_eq!(
+            stats.color(ServiceColor::red()),
+            Some(SpawnCounts {
+                accepted: 1,
+                rejected: 1
+            })
+        );
+        assert_eq!(
+            stats.color(ServiceColor::green()),
+            Some(SpawnCounts::default())
+        );
+        assert_eq!(stats.colors().count(), 2);
+    }
+
+    #[test]
+    fn test_heatmap_flashes_and_fades() {
+        let mut heatmap = RejectionHeatmap::new(4, 4, 100);
+        heatmap.record(2, 3);
+        heatmap.record(9, 9);
+        assert_eq!(heatmap.intensity(2, 3), 255);
+
+        heatmap.fade();
+        assert_eq!(heatmap.intensity(2, 3), 155);
+        heatmap.fade();
+        heatmap.fade();
+        assert_eq!(heatmap.intensity(2, 3), 0);
+        assert!(heatmap.as_slice().iter().all(|&v| v == 0));
+    }
+}
@@ -0,0 +1,368 @@
+//! Admission - topographic admission control for tower services
+//! (feature `tower`)
+//!
+//! [`AdmissionLayer`] puts a [`Space`] in front of a request handler. Each
+//! request is mapped to a cell by a [`CellKey`]; if the cell is habitable the
+//! request leaves trace in the service's color and is forwarded, otherwise it
+//! is rejected with [`Overloaded`] before the inner service sees it (A1).
+//!
+//! Check and contribution are a single atomic step
+//! ([`Space::try_contribute`]), so concurrent requests cannot overshoot the
+//! threshold. The space must be ticked (e.g. by a
+//! [`TickDriver`](crate::TickDriver)) for trace to decay and cells to
+//! reopen. Requests mapped outside the space are rejected.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use tes::admission::AdmissionLayer;
+//! use tes::{ServiceColor, Space};
+//!
+//! let space = Arc::new(Space::new(64, 64, 5, 1000));
+//! let layer = AdmissionLayer::new(space, |path: &String| (path.len() % 64, 0), 100)
+//!     .color(ServiceColor::from_name("AuthService"));
+//! # let _ = layer;
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use pin_project_lite::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::field::TraceField;
+use crate::grid::DensityGrid;
+use crate::isotope::ServiceColor;
+use crate::space::Space;
+
+/// Maps a request to the cell it loads.
+///
+/// Implemented for every `Fn(&Req) -> (usize, usize)`.
+pub trait CellKey<Req> {
+    /// Cell `(x, y)` of `request`.
+    fn cell(&self, request: &Req) -> (usize, usize);
+}
+
+impl<Req, K> CellKey<Req> for K
+where
+    K: Fn(&Req) -> (usize, usize),
+{
+    fn cell(&self, request: &Req) -> (usize, usize) {
+        self(request)
+    }
+}
+
+/// A request was rejected because its cell is not habitable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overloaded {
+    /// Cell the request was mapped to
+    pub x: usize,
+    /// Cell the request was mapped to
+    pub y: usize,
+}
+
+impl fmt::Display for Overloaded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cell ({}, {}) is not habitable", self.x, self.y)
+    }
+}
+
+impl Error for Overloaded {}
+
+/// Error of an [`Admission`] service.
+#[derive(Debug)]
+pub enum AdmissionError<E> {
+    /// Rejected before reaching the inner service
+    Overloaded(Overloaded),
+    /// Inner service failed
+    Inner(E),
+}
+
+impl<E> AdmissionError<E> {
+    /// Check if the request was rejected by admission control.
+    pub fn is_overloaded(&self) -> bool {
+        matches!(self, Self::Overloaded(_))
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for AdmissionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overloaded(e) => write!(f, "overloaded: {}", e),
+            Self::Inner(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for AdmissionError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Overloaded(e) => Some(e),
+            Self::Inner(e) => Some(e),
+        }
+    }
+}
+
+/// Admission parameters shared by the layer and its services.
+struct Gate<K, F> {
+    space: Arc<Space<F>>,
+    key: K,
+    color: ServiceColor,
+    amount: u32,
+}
+
+impl<K: Clone, F> Clone for Gate<K, F> {
+    fn clone(&self) -> Self {
+        Self {
+            space: Arc::clone(&self.space),
+            key: self.key.clone(),
+            color: self.color,
+            amount: self.amount,
+        }
+    }
+}
+
+/// [`Layer`] wrapping services in [`Admission`].
+pub struct AdmissionLayer<K, F = DensityGrid> {
+    gate: Gate<K, F>,
+}
+
+impl<K, F> AdmissionLayer<K, F> {
+    /// Admit requests into `space`, each contributing `amount` at the cell
+    /// chosen by `key`.
+    ///
+    /// Trace is neutral until a color is set with [`AdmissionLayer::color`].
+    pub fn new(space: Arc<Space<F>>, key: K, amount: u32) -> Self {
+        Self {
+            gate: Gate {
+                space,
+                key,
+                color: ServiceColor::neutral(),
+                amount,
+            },
+        }
+    }
+
+    /// Set the color of the trace left by admitted requests.
+    pub fn color(mut self, color: ServiceColor) -> Self {
+        self.gate.color = color;
+        self
+    }
+
+    /// Get the space requests are admitted into.
+    pub fn space(&self) -> &Arc<Space<F>> {
+        &self.gate.space
+    }
+}
+
+impl<K: Clone, F> Clone for AdmissionLayer<K, F> {
+    fn clone(&self) -> Self {
+        Self {
+            gate: self.gate.clone(),
+        }
+    }
+}
+
+impl<S, K: Clone, F> Layer<S> for AdmissionLayer<K, F> {
+    type Service = Admission<S, K, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Admission {
+            inner,
+            gate: self.gate.clone(),
+        }
+    }
+}
+
+/// Service rejecting requests whose cell is saturated.
+pub struct Admission<S, K, F = DensityGrid> {
+    inner: S,
+    gate: Gate<K, F>,
+}
+
+impl<S, K, F> Admission<S, K, F> {
+    /// Get the wrapped service.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwrap the inner service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Clone, K: Clone, F> Clone for Admission<S, K, F> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            gate: self.gate.clone(),
+        }
+    }
+}
+
+impl<S, K, F, Req> Service<Req> for Admission<S, K, F>
+where
+    S: Service<Req>,
+    K: CellKey<Req>,
+    F: TraceField,
+{
+    type Response = S::Response;
+    type Error = AdmissionError<S::Error>;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(AdmissionError::Inner)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        let (x, y) = self.gate.key.cell(&request);
+        let gate = &self.gate;
+        let kind = if gate.space.try_contribute(x, y, gate.amount, gate.color) {
+            Kind::Admitted {
+                future: self.inner.call(request),
+            }
+        } else {
+            Kind::Rejected {
+                error: Overloaded { x, y },
+            }
+        };
+        ResponseFuture { kind }
+    }
+}
+
+pin_project! {
+    /// Response future of [`Admission`].
+    pub struct ResponseFuture<Fut> {
+        #[pin]
+        kind: Kind<Fut>,
+    }
+}
+
+pin_project! {
+    #[project = KindProj]
+    enum Kind<Fut> {
+        Admitted { #[pin] future: Fut },
+        Rejected { error: Overloaded },
+    }
+}
+
+impl<Fut, T, E> Future for ResponseFuture<Fut>
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    type Output = Result<T, AdmissionError<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().kind.project() {
+            KindProj::Admitted { future } => future.poll(cx).map_err(AdmissionError::Inner),
+            KindProj::Rejected { error } => Poll::Ready(Err(AdmissionError::Overloaded(*error))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+    use std::task::Waker;
+
+    /// Mock handler counting the requests that reach it.
+    #[derive(Clone, Default)]
+    struct Echo {
+        calls: usize,
+    }
+
+    impl Service<u32> for Echo {
+        type Response = u32;
+        type Error = Infallible;
+        type Future = Ready<Result<u32, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: u32) -> Self::Future {
+            self.calls += 1;
+            ready(Ok(request))
+        }
+    }
+
+    fn block_on<Fut: Future>(future: Fut) -> Fut::Output {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    fn send<S: Service<u32>>(service: &mut S, request: u32) -> Result<S::Response, S::Error> {
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(service.poll_ready(&mut cx).is_ready());
+        block_on(service.call(request))
+    }
+
+    #[test]
+    fn test_rejects_saturated_cell() {
+        let space = Arc::new(Space::new(4, 1, 0, 250));
+        let layer = AdmissionLayer::new(Arc::clone(&space), |r: &u32| (*r as usize, 0), 100);
+        let mut service = layer.layer(Echo::default());
+
+        // 0 → 100 → 200 → 300: the fourth request finds the cell saturated
+        for _ in 0..3 {
+            assert_eq!(send(&mut service, 1).unwrap(), 1);
+        }
+        let err = send(&mut service, 1).unwrap_err();
+        assert!(err.is_overloaded());
+        assert_eq!(err.to_string(), "overloaded: cell (1, 0) is not habitable");
+        assert_eq!(service.get_ref().calls, 3);
+        assert_eq!(space.density(1, 0), 300);
+
+        // Other cells are unaffected; off-grid keys are rejected
+        assert!(send(&mut service, 2).is_ok());
+        assert!(matches!(
+            send(&mut service, 9),
+            Err(AdmissionError::Overloaded(Overloaded { x: 9, y: 0 }))
+        ));
+    }
+
+    #[test]
+    fn test_colors_trace_and_reopens_after_decay() {
+        let space = Arc::new(Space::with_isotopes(2, 2, 100, 100));
+        let mut service = AdmissionLayer::new(Arc::clone(&space), |_: &u32| (0, 0), 100)
+            .color(ServiceColor::green())
+            .layer(Echo::default());
+
+        assert!(send(&mut service, 7).is_ok());
+        assert!(send(&mut service, 7).is_err());
+        assert_eq!(space.field().rgb(0, 0), (0, 100, 0));
+
+        space.tick();
+        assert!(send(&mut service, 7).is_ok());
+    }
+
+    #[test]
+    fn test_concurrent_requests_respect_threshold() {
+        let space = Arc::new(Space::new(1, 1, 0, 1000));
+        let layer = AdmissionLayer::new(Arc::clone(&space), |_: &u32| (0, 0), 10);
+
+        let admitted: usize = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    let mut service = layer.layer(Echo::default());
+                    s.spawn(move || (0..100).filter(|&i| send(&mut service, i).is_ok()).count())
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(admitted, 100);
+        assert_eq!(space.density(0, 0), 1000);
+    }
+}
@@ -0,0 +1,250 @@
+//! TES Terminal Observation Plane
+//!
+//! Watches a live substrate from a terminal (e.g. over SSH). Two field rows
+//! per line with Unicode half-blocks in truecolor, the viz palette via
+//! `tes::render`, and the telemetry band of docs/gui.md below the field.
+//!
+//! Keys: `r` / `g` / `b` toggle channel filters, space pauses, `q` quits.
+//!
+//! Run with: cargo run --bin tes-tui --features tui
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+
+use tes::{Frame, IsotopeGrid, ServiceColor, Substrate};
+
+// Frame rate limiting (~30 FPS is plenty for a terminal)
+const FRAME_TIME: Duration = Duration::from_millis(33);
+
+// Substrate parameters
+const DECAY_RATE: u32 = 5;
+const HABITABILITY_THRESHOLD: u32 = 2500;
+const SPAWNS_PER_TICK: usize = 90;
+const LIFETIME: u32 = 12;
+const DIFFUSION_STEPS: usize = 2;
+
+// Hairline + telemetry line
+const BAND_ROWS: u16 = 2;
+
+/// Service hotspot, in fractions of the field size
+struct Hotspot {
+    color: ServiceColor,
+    center: (f32, f32),
+    radius: f32,
+    contribution: u32,
+}
+
+/// Channels shown; filtered channels are zeroed in every pixel.
+struct Filter {
+    channels: [bool; 3],
+}
+
+impl Filter {
+    fn toggle(&mut self, channel: usize) {
+        self.channels[channel] = !self.channels[channel];
+    }
+
+    fn apply(&self, rgba: &[u8]) -> Color {
+        let c = |i: usize| if self.channels[i] { rgba[i] } else { 0 };
+        Color::Rgb {
+            r: c(0),
+            g: c(1),
+            b: c(2),
+        }
+    }
+
+    fn label(&self) -> String {
+        "RGB"
+            .chars()
+            .zip(self.channels)
+            .map(|(c, on)| if on { c } else { '-' })
+            .collect()
+    }
+}
+
+/// Xorshift - enough randomness for spawn positions without a dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Raw mode and alternate screen, restored on drop (also on panic).
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn main() -> io::Result<()> {
+    // One field cell per column, two per line above the band
+    let (cols, rows) = terminal::size()?;
+    let width = usize::from(cols).max(8);
+    let height = usize::from(rows.saturating_sub(BAND_ROWS)).max(4) * 2;
+
+    let mut substrate: Substrate<(), IsotopeGrid> =
+        Substrate::with_isotopes(width, height, DECAY_RATE, HABITABILITY_THRESHOLD);
+    substrate.track_rejections(u8::MAX / 4);
+
+    let hotspots = [
+        Hotspot {
+            color: ServiceColor::from_name("Auth"),
+            center: (0.27, 0.27),
+            radius: 0.18,
+            contribution: 60,
+        },
+        Hotspot {
+            color: ServiceColor::from_name("Payment"),
+            center: (0.5, 0.5),
+            radius: 0.22,
+            contribution: 75,
+        },
+        Hotspot {
+            color: ServiceColor::from_name("Worker"),
+            center: (0.74, 0.74),
+            radius: 0.16,
+            contribution: 55,
+        },
+    ];
+
+    let _screen = Screen::enter()?;
+    let mut out = io::BufWriter::new(io::stdout());
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let mut frame = Frame::of(substrate.space().field(), HABITABILITY_THRESHOLD);
+    let mut filter = Filter {
+        channels: [true; 3],
+    };
+    let mut paused = false;
+
+    loop {
+        // === Input: drain events until the frame deadline ===
+        let deadline = Instant::now() + FRAME_TIME;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            if !event::poll(timeout)? {
+                break;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(())
+                    }
+                    KeyCode::Char('r') => filter.toggle(0),
+                    KeyCode::Char('g') => filter.toggle(1),
+                    KeyCode::Char('b') => filter.toggle(2),
+                    KeyCode::Char(' ') => paused = !paused,
+                    _ => {}
+                },
+                Event::Resize(..) => queue!(out, terminal::Clear(terminal::ClearType::All))?,
+                _ => {}
+            }
+        }
+
+        // === Simulation: spawn around hotspots, diffuse, tick ===
+        if !paused {
+            for hotspot in &hotspots {
+                for _ in 0..SPAWNS_PER_TICK / hotspots.len() {
+                    let angle = rng.next_f32() * std::f32::consts::TAU;
+                    let dist = rng.next_f32() * hotspot.radius;
+                    let x = (hotspot.center.0 + angle.cos() * dist) * width as f32;
+                    let y = (hotspot.center.1 + angle.sin() * dist) * height as f32;
+                    substrate.spawn_colored(
+                        x as usize,
+                        y as usize,
+                        LIFETIME,
+                        hotspot.contribution,
+                        hotspot.color,
+                    );
+                }
+            }
+            for _ in 0..DIFFUSION_STEPS {
+                substrate.space().diffuse();
+            }
+            substrate.tick();
+        }
+
+        // === Render: field as half-blocks, then the telemetry band ===
+        let field = substrate.space().field();
+        let heatmap = substrate.spawn_stats().heatmap();
+        frame.redraw(field, HABITABILITY_THRESHOLD, heatmap);
+        let line = draw(&mut out, &frame, &filter)?;
+
+        let stats = substrate.stats();
+        let (cols, _) = terminal::size()?;
+        let band = format!(
+            " ENTROPY: {:.2} | PHASE: {} | SOLID%: {:.1} | DECAY: {:.3} | {} | TICK {} | REJECTED {}{}",
+            stats.entropy,
+            format!("{:?}", stats.phase()).to_uppercase(),
+            stats.solid_pct(),
+            DECAY_RATE as f32 / 1000.0,
+            filter.label(),
+            substrate.tick_count(),
+            substrate.spawn_stats().last_tick().rejected,
+            if paused { " | PAUSED" } else { "" },
+        );
+        queue!(
+            out,
+            SetForegroundColor(Color::White),
+            SetBackgroundColor(Color::Black),
+            cursor::MoveTo(0, line),
+            Print("─".repeat(usize::from(cols))),
+            cursor::MoveTo(0, line + 1),
+            Print(format!("{:<1$.1$}", band, usize::from(cols))),
+            ResetColor,
+        )?;
+        out.flush()?;
+    }
+}
+
+/// Draw `frame` with `▀`: foreground is the upper cell, background the lower.
+///
+/// Returns the number of lines drawn (the band goes below them).
+fn draw<W: Write>(out: &mut W, frame: &Frame, filter: &Filter) -> io::Result<u16> {
+    let (width, height) = frame.dimensions();
+    let (cols, rows) = terminal::size()?;
+    let cols = width.min(usize::from(cols));
+    let lines = (height / 2).min(usize::from(rows.saturating_sub(BAND_ROWS)));
+    let pixel = |x: usize, y: usize| {
+        let i = (y * width + x) * 4;
+        filter.apply(&frame.pixels()[i..i + 4])
+    };
+
+    for line in 0..lines {
+        queue!(out, cursor::MoveTo(0, line as u16))?;
+        let mut colors = None;
+        for x in 0..cols {
+            let cell = (pixel(x, 2 * line), pixel(x, 2 * line + 1));
+            if colors != Some(cell) {
+                queue!(out, SetForegroundColor(cell.0), SetBackgroundColor(cell.1))?;
+                colors = Some(cell);
+            }
+            queue!(out, Print('▀'))?;
+        }
+    }
+    Ok(lines as u16)
+}
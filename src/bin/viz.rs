@@ -14,7 +14,7 @@ use winit::{
     window::WindowBuilder,
 };
 
-use tes::{IsotopeGrid, ServiceColor};
+use tes::{Frame, IsotopeGrid, RejectionHeatmap, ServiceColor, Slit, SlitScan, TraceField};
 
 // Frame rate limiting (60 FPS)
 const TARGET_FRAME_TIME: Duration = Duration::from_millis(16);
@@ -25,6 +25,14 @@ const GRID_HEIGHT: usize = 256;
 const DECAY_RATE: u32 = 5; // Balanced: 5x faster recovery with high energy
 const HABITABILITY_THRESHOLD: u32 = 2500; // Earlier choking for backpressure
 
+// Temporal flux: a diagonal cut through all three hotspots, one column per
+// tick, drawn below the field (docs/gui.md 3.2)
+const SLIT: Slit = Slit::Line {
+    from: (70, 70),
+    to: (190, 190),
+};
+const SLIT_DEPTH: usize = GRID_WIDTH;
+
 // Contribution parameters
 const CONTRIBUTIONS_PER_TICK: usize = 400; // Faster saturation for demo
 
@@ -42,11 +50,18 @@ fn main() {
 }
 
 async fn run() {
+    // Field on top, slit-scan timeline below, in one texture
+    let slit_len = SLIT.positions(GRID_WIDTH, GRID_HEIGHT).len();
+    let texture_height = GRID_HEIGHT + slit_len;
+
     let event_loop = EventLoop::new().unwrap();
     let window = Arc::new(
         WindowBuilder::new()
             .with_title("TES - Pure Field Visualization")
-            .with_inner_size(winit::dpi::LogicalSize::new(800, 800))
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                600,
+                (600 * texture_height / GRID_WIDTH) as u32,
+            ))
             .build(&event_loop)
             .unwrap(),
     );
@@ -84,7 +99,7 @@ async fn run() {
         label: Some("Field Texture"),
         size: wgpu::Extent3d {
             width: GRID_WIDTH as u32,
-            height: GRID_HEIGHT as u32,
+            height: texture_height as u32,
             depth_or_array_layers: 1,
         },
         mip_level_count: 1,
@@ -210,10 +225,14 @@ async fn run() {
     let mut last_frame = Instant::now();
 
     // Pixel buffer - reused every frame
-    let mut pixels = vec![0u8; GRID_WIDTH * GRID_HEIGHT * 4];
+    let mut frame = Frame::of(&grid, HABITABILITY_THRESHOLD);
+
+    // Slit-scan recorder and its timeline image
+    let mut slit_scan = SlitScan::new(&grid, SLIT, SLIT_DEPTH);
+    let mut timeline = Frame::slit_scan(&slit_scan, HABITABILITY_THRESHOLD);
 
     // Rejection flash grid - tracks where shapes got blocked this tick
-    let mut rejection_flash = vec![0u8; GRID_WIDTH * GRID_HEIGHT];
+    let mut rejection_flash = RejectionHeatmap::new(GRID_WIDTH, GRID_HEIGHT, u8::MAX);
 
     event_loop
         .run(move |event, elwt| {
@@ -238,7 +257,7 @@ async fn run() {
                     tick += 1;
 
                     // Clear rejection flash from previous tick
-                    rejection_flash.fill(0);
+                    rejection_flash.fade();
                     let mut rejected_count = 0usize;
 
                     // === PHASE 1: Direct Field Contribution ===
@@ -257,7 +276,7 @@ async fn run() {
                                     grid.contribute(x, y, hotspot.intensity, hotspot.color);
                                 } else {
                                     // REJECTION! Mark this location for flash
-                                    rejection_flash[y * GRID_WIDTH + x] = 255;
+                                    rejection_flash.record(x, y);
                                     rejected_count += 1;
                                 }
                             }
@@ -275,38 +294,14 @@ async fn run() {
                     grid.apply_decay();
 
                     // === PHASE 4: Render Field ===
-                    let mut saturated_count = 0usize;
-                    for y in 0..GRID_HEIGHT {
-                        for x in 0..GRID_WIDTH {
-                            let (r, g, b) = grid.rgb(x, y);
-                            let density = grid.density(x, y);
-                            let idx = (y * GRID_WIDTH + x) * 4;
-                            let flash = rejection_flash[y * GRID_WIDTH + x];
-
-                            if flash > 0 {
-                                // REJECTION FLASH: Magenta spark where shape got blocked
-                                pixels[idx] = 255; // R
-                                pixels[idx + 1] = 0; // G
-                                pixels[idx + 2] = 255; // B (Magenta)
-                            } else if density >= HABITABILITY_THRESHOLD {
-                                // Saturated zone - white wall
-                                pixels[idx] = 255;
-                                pixels[idx + 1] = 255;
-                                pixels[idx + 2] = 255;
-                                saturated_count += 1;
-                            } else {
-                                // Normal trace with danger ramp
-                                let scale =
-                                    |v: u32| ((v * 255) / HABITABILITY_THRESHOLD).min(255) as u8;
-                                pixels[idx] = scale(r);
-                                pixels[idx + 1] = scale(g);
-                                pixels[idx + 2] = scale(b);
-                            }
-                            pixels[idx + 3] = 255;
-                        }
-                    }
+                    // Magenta flashes, white walls, danger ramp (tes::render)
+                    frame.redraw(&grid, HABITABILITY_THRESHOLD, Some(&rejection_flash));
+
+                    // === PHASE 5: Record Temporal Flux ===
+                    slit_scan.record(&grid);
+                    timeline.redraw_slit_scan(&slit_scan, HABITABILITY_THRESHOLD);
 
-                    // Single texture upload
+                    // Texture upload: field, then timeline below it
                     queue.write_texture(
                         wgpu::ImageCopyTexture {
                             texture: &texture,
@@ -314,7 +309,7 @@ async fn run() {
                             origin: wgpu::Origin3d::ZERO,
                             aspect: wgpu::TextureAspect::All,
                         },
-                        &pixels,
+                        frame.pixels(),
                         wgpu::ImageDataLayout {
                             offset: 0,
                             bytes_per_row: Some(GRID_WIDTH as u32 * 4),
@@ -326,6 +321,29 @@ async fn run() {
                             depth_or_array_layers: 1,
                         },
                     );
+                    queue.write_texture(
+                        wgpu::ImageCopyTexture {
+                            texture: &texture,
+                            mip_level: 0,
+                            origin: wgpu::Origin3d {
+                                x: 0,
+                                y: GRID_HEIGHT as u32,
+                                z: 0,
+                            },
+                            aspect: wgpu::TextureAspect::All,
+                        },
+                        timeline.pixels(),
+                        wgpu::ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: Some(SLIT_DEPTH as u32 * 4),
+                            rows_per_image: Some(slit_len as u32),
+                        },
+                        wgpu::Extent3d {
+                            width: SLIT_DEPTH as u32,
+                            height: slit_len as u32,
+                            depth_or_array_layers: 1,
+                        },
+                    );
 
                     // Render
                     let output = surface.get_current_texture().unwrap();
@@ -364,12 +382,17 @@ async fn run() {
 
                     // Telemetry - throttled
                     if tick % 30 == 0 {
-                        let saturated_pct =
-                            (saturated_count as f32 / (GRID_WIDTH * GRID_HEIGHT) as f32) * 100.0;
+                        let stats = grid.stats();
                         let mode = if tick > 300 { "LOAD TEST 3x" } else { "Normal" };
                         window.set_title(&format!(
-                            "TES | {} | Tick: {} | Sat: {:.1}% | Rejected: {}",
-                            mode, tick, saturated_pct, rejected_count
+                            "TES | {} | Tick: {} | ENTROPY: {:.2} | PHASE: {:?} | SOLID%: {:.1} | DECAY: {:.3} | Rejected: {}",
+                            mode,
+                            tick,
+                            stats.entropy,
+                            stats.phase(),
+                            stats.solid_pct(),
+                            DECAY_RATE as f32 / 1000.0,
+                            rejected_count
                         ));
                     }
 
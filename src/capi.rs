@@ -0,0 +1,514 @@
+//! C API - opaque-handle bindings for non-Rust services (feature `capi`)
+//!
+//! Grids and substrates are heap handles created by `tes_*_new` /
+//! `tes_*_restore` and released with `tes_*_free`. Densities and colors use
+//! the crate's fixed point (1000 = 1.0). The C header is generated by
+//! cbindgen at build time and committed as `capi/tes.h`.
+//!
+//! A grid handle may be shared between threads for contribute and query
+//! calls (A0); decay, diffuse and every substrate call need exclusive
+//! access.
+//!
+//! Build a static library for linking from C:
+//!
+//! ```text
+//! cargo rustc --release --features capi --crate-type staticlib
+//! ```
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use crate::grid::{Regime, RegimeThresholds};
+use crate::isotope::{IsotopeGrid, ServiceColor};
+use crate::snapshot::Snapshot;
+use crate::substrate::Substrate;
+
+/// Opaque handle to an RGB isotope grid.
+pub struct TesGrid(IsotopeGrid);
+
+/// Opaque handle to a substrate living on an isotope grid.
+pub struct TesSubstrate(Substrate<(), IsotopeGrid>);
+
+/// Trace color, one fixed-point weight per channel.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TesColor {
+    pub r: u32,
+    pub g: u32,
+    pub b: u32,
+}
+
+/// Physical regime of a cell (A5).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TesRegime {
+    Solid,
+    Liquid,
+    Gas,
+}
+
+/// Result of calls that can fail without returning a handle.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TesStatus {
+    Ok,
+    /// A required pointer was NULL
+    Null,
+    /// Output buffer is smaller than the length written to `len`
+    BufferTooSmall,
+}
+
+impl From<ServiceColor> for TesColor {
+    fn from(c: ServiceColor) -> Self {
+        Self {
+            r: c.r,
+            g: c.g,
+            b: c.b,
+        }
+    }
+}
+
+impl From<TesColor> for ServiceColor {
+    fn from(c: TesColor) -> Self {
+        Self {
+            r: c.r,
+            g: c.g,
+            b: c.b,
+        }
+    }
+}
+
+impl From<Regime> for TesRegime {
+    fn from(r: Regime) -> Self {
+        match r {
+            Regime::Solid => Self::Solid,
+            Regime::Liquid => Self::Liquid,
+            Regime::Gas => Self::Gas,
+        }
+    }
+}
+
+impl From<(u32, u32, u32)> for TesColor {
+    fn from((r, g, b): (u32, u32, u32)) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// Move a handle to the heap, or NULL on error.
+fn into_handle<T, E>(value: Result<T, E>) -> *mut T {
+    value.map_or(ptr::null_mut(), |v| Box::into_raw(Box::new(v)))
+}
+
+/// Copy a snapshot into a caller buffer. `len` always receives its size, so
+/// callers can size the buffer with `buf = NULL, cap = 0`.
+///
+/// # Safety
+///
+/// `buf` must be valid for `cap` bytes writes when `cap` is large enough;
+/// `len` must be valid for writes.
+unsafe fn copy_out(bytes: &[u8], buf: *mut u8, cap: usize, len: *mut usize) -> TesStatus {
+    if len.is_null() {
+        return TesStatus::Null;
+    }
+    *len = bytes.len();
+    if cap < bytes.len() || buf.is_null() {
+        return TesStatus::BufferTooSmall;
+    }
+    ptr::copy_nonoverlapping(bytes.as_ptr(), buf, bytes.len());
+    TesStatus::Ok
+}
+
+/// Borrow a snapshot buffer; NULL is only allowed when `len` is 0.
+///
+/// # Safety
+///
+/// `buf` must be valid for `len` bytes reads.
+unsafe fn bytes<'a>(buf: *const u8, len: usize) -> &'a [u8] {
+    if buf.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(buf, len)
+    }
+}
+
+/// Deterministic color for a service name (NUL-terminated).
+///
+/// Returns the neutral color for NULL.
+///
+/// # Safety
+///
+/// `name` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tes_color_from_name(name: *const c_char) -> TesColor {
+    if name.is_null() {
+        return ServiceColor::neutral().into();
+    }
+    ServiceColor::from_name(&CStr::from_ptr(name).to_string_lossy()).into()
+}
+
+/// Create a grid. Returns NULL if the parameters are rejected by
+/// [`IsotopeGrid::try_new`].
+#[no_mangle]
+pub extern "C" fn tes_grid_new(
+    width: usize,
+    height: usize,
+    decay_rate: u32,
+    solid_threshold: u32,
+    liquid_threshold: u32,
+) -> *mut TesGrid {
+    into_handle(
+        IsotopeGrid::try_new(width, height, decay_rate, solid_threshold, liquid_threshold)
+            .map(TesGrid),
+    )
+}
+
+/// Release a grid. NULL is ignored.
+///
+/// # Safety
+///
+/// `grid` must be NULL or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_free(grid: *mut TesGrid) {
+    if !grid.is_null() {
+        drop(Box::from_raw(grid));
+    }
+}
+
+/// Width of the grid.
+///
+/// # Safety
+///
+/// `grid` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_width(grid: *const TesGrid) -> usize {
+    (*grid).0.dimensions().0
+}
+
+/// Height of the grid.
+///
+/// # Safety
+///
+/// `grid` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_height(grid: *const TesGrid) -> usize {
+    (*grid).0.dimensions().1
+}
+
+/// Leave `amount` of trace in `color` at a cell. Off-grid cells are ignored.
+///
+/// # Safety
+///
+/// `grid` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_contribute(
+    grid: *const TesGrid,
+    x: usize,
+    y: usize,
+    amount: u32,
+    color: TesColor,
+) {
+    (*grid).0.contribute(x, y, amount, color.into());
+}
+
+/// Contribute only if the cell is below `threshold` (A1). Check and
+/// contribution are one atomic step.
+///
+/// # Safety
+///
+/// `grid` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_try_contribute(
+    grid: *const TesGrid,
+    x: usize,
+    y: usize,
+    amount: u32,
+    color: TesColor,
+    threshold: u32,
+) -> bool {
+    (*grid)
+        .0
+        .try_contribute(x, y, amount, color.into(), threshold)
+}
+
+/// Total density at a cell (0 off-grid).
+///
+/// # Safety
+///
+/// `grid` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_density(grid: *const TesGrid, x: usize, y: usize) -> u32 {
+    (*grid).0.density(x, y)
+}
+
+/// Regime of a cell (gas off-grid).
+///
+/// # Safety
+///
+/// `grid` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_regime(grid: *const TesGrid, x: usize, y: usize) -> TesRegime {
+    (*grid).0.regime(x, y).into()
+}
+
+/// Per-channel density of a cell.
+///
+/// # Safety
+///
+/// `grid` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_rgb(grid: *const TesGrid, x: usize, y: usize) -> TesColor {
+    (*grid).0.rgb(x, y).into()
+}
+
+/// Apply one tick of decay (A3).
+///
+/// # Safety
+///
+/// `grid` must be a valid handle with no concurrent calls.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_decay(grid: *mut TesGrid) {
+    (*grid).0.apply_decay();
+}
+
+/// Apply one step of diffusion.
+///
+/// # Safety
+///
+/// `grid` must be a valid handle with no concurrent calls.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_diffuse(grid: *mut TesGrid) {
+    (*grid).0.diffuse();
+}
+
+/// Write a snapshot of the grid into `buf`.
+///
+/// `len` receives the snapshot size even when `cap` is too small, so the
+/// buffer can be sized with a first call passing `buf = NULL, cap = 0`.
+///
+/// # Safety
+///
+/// `grid` must be a valid handle, `buf` valid for `cap` bytes and `len`
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_snapshot(
+    grid: *const TesGrid,
+    buf: *mut u8,
+    cap: usize,
+    len: *mut usize,
+) -> TesStatus {
+    if grid.is_null() {
+        return TesStatus::Null;
+    }
+    copy_out(&(*grid).0.to_snapshot(), buf, cap, len)
+}
+
+/// Restore a grid from a snapshot. Returns NULL on corrupt input.
+///
+/// # Safety
+///
+/// `buf` must be valid for `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tes_grid_restore(buf: *const u8, len: usize) -> *mut TesGrid {
+    into_handle(IsotopeGrid::from_snapshot(bytes(buf, len)).map(TesGrid))
+}
+
+/// Create a substrate. Shapes are admitted where density is below
+/// `solid_threshold`. Returns NULL on invalid parameters.
+#[no_mangle]
+pub extern "C" fn tes_substrate_new(
+    width: usize,
+    height: usize,
+    decay_rate: u32,
+    solid_threshold: u32,
+    liquid_threshold: u32,
+) -> *mut TesSubstrate {
+    let thresholds = RegimeThresholds::new(solid_threshold, liquid_threshold);
+    into_handle(
+        Substrate::try_with_isotopes(width, height, decay_rate, thresholds).map(TesSubstrate),
+    )
+}
+
+/// Release a substrate. NULL is ignored.
+///
+/// # Safety
+///
+/// `substrate` must be NULL or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_free(substrate: *mut TesSubstrate) {
+    if !substrate.is_null() {
+        drop(Box::from_raw(substrate));
+    }
+}
+
+/// Spawn a shape contributing in `color`.
+///
+/// Returns the shape ID, or 0 if the position is not habitable (A1).
+///
+/// # Safety
+///
+/// `substrate` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_spawn(
+    substrate: *mut TesSubstrate,
+    x: usize,
+    y: usize,
+    lifetime: u32,
+    contribution: u32,
+    color: TesColor,
+) -> u64 {
+    (*substrate)
+        .0
+        .spawn_colored(x, y, lifetime, contribution, color.into())
+        .unwrap_or(0)
+}
+
+/// Advance one tick: shapes contribute and age, then the field decays.
+///
+/// # Safety
+///
+/// `substrate` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_tick(substrate: *mut TesSubstrate) {
+    (*substrate).0.tick();
+}
+
+/// Ticks elapsed.
+///
+/// # Safety
+///
+/// `substrate` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_tick_count(substrate: *const TesSubstrate) -> u64 {
+    (*substrate).0.tick_count()
+}
+
+/// Number of living shapes.
+///
+/// # Safety
+///
+/// `substrate` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_shape_count(substrate: *const TesSubstrate) -> usize {
+    (*substrate).0.shape_count()
+}
+
+/// Total density at a cell of the substrate's field.
+///
+/// # Safety
+///
+/// `substrate` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_density(
+    substrate: *const TesSubstrate,
+    x: usize,
+    y: usize,
+) -> u32 {
+    (*substrate).0.space().density(x, y)
+}
+
+/// Regime of a cell of the substrate's field.
+///
+/// # Safety
+///
+/// `substrate` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_regime(
+    substrate: *const TesSubstrate,
+    x: usize,
+    y: usize,
+) -> TesRegime {
+    (*substrate).0.space().regime(x, y).into()
+}
+
+/// Write a snapshot of the substrate's terrain and tick count (living
+/// shapes are not recorded). Buffer handling as in [`tes_grid_snapshot`].
+///
+/// # Safety
+///
+/// `substrate` must be a valid handle, `buf` valid for `cap` bytes and
+/// `len` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_snapshot(
+    substrate: *const TesSubstrate,
+    buf: *mut u8,
+    cap: usize,
+    len: *mut usize,
+) -> TesStatus {
+    if substrate.is_null() {
+        return TesStatus::Null;
+    }
+    copy_out(&(*substrate).0.to_snapshot(), buf, cap, len)
+}
+
+/// Restore a substrate from a snapshot. Returns NULL on corrupt input.
+///
+/// # Safety
+///
+/// `buf` must be valid for `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tes_substrate_restore(buf: *const u8, len: usize) -> *mut TesSubstrate {
+    into_handle(Substrate::from_snapshot(bytes(buf, len)).map(TesSubstrate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::c_int;
+
+    #[link(name = "tes_capi_test", kind = "static")]
+    extern "C" {
+        fn tes_capi_selftest() -> c_int;
+    }
+
+    #[test]
+    fn test_c_program() {
+        assert_eq!(unsafe { tes_capi_selftest() }, 0);
+    }
+
+    #[test]
+    fn test_committed_header_is_current() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/tes.h"));
+        let committed = include_str!("../capi/tes.h");
+        assert!(
+            generated == committed,
+            "capi/tes.h is stale; copy it from {}",
+            concat!(env!("OUT_DIR"), "/tes.h")
+        );
+    }
+
+    #[test]
+    fn test_grid_handles() {
+        unsafe {
+            assert!(tes_grid_new(0, 4, 1, 1000, 500).is_null());
+            let grid = tes_grid_new(4, 4, 10, 1000, 500);
+            assert!(!grid.is_null());
+
+            let red = ServiceColor::red().into();
+            assert!(tes_grid_try_contribute(grid, 1, 1, 1200, red, 1000));
+            assert!(!tes_grid_try_contribute(grid, 1, 1, 10, red, 1000));
+            assert_eq!(tes_grid_regime(grid, 1, 1), TesRegime::Solid);
+            tes_grid_decay(grid);
+            assert_eq!(
+                tes_grid_rgb(grid, 1, 1),
+                TesColor {
+                    r: 1190,
+                    g: 0,
+                    b: 0
+                }
+            );
+
+            let mut len = 0;
+            let status = tes_grid_snapshot(grid, ptr::null_mut(), 0, &mut len);
+            assert_eq!(status, TesStatus::BufferTooSmall);
+            let mut buf = vec![0u8; len];
+            let status = tes_grid_snapshot(grid, buf.as_mut_ptr(), buf.len(), &mut len);
+            assert_eq!(status, TesStatus::Ok);
+            let copy = tes_grid_restore(buf.as_ptr(), len);
+            assert_eq!(tes_grid_density(copy, 1, 1), 1190);
+            assert!(tes_grid_restore(buf.as_ptr(), 3).is_null());
+
+            tes_grid_free(copy);
+            tes_grid_free(grid);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Concurrent Substrate - shared spawn from many threads
+//!
+//! `Substrate` needs `&mut self` to admit work. `ConcurrentSubstrate` lets
+//! any number of threads spawn through `&self` while a single ticker thread
+//! advances time:
+//!
+//! - Admission is one atomic check-and-contribute on the field (A1 can not
+//!   be raced past the threshold).
+//! - Admitted shapes go into a lock-free queue and are merged into the
+//!   living population at the next tick boundary.
+//! - Only the ticker touches the living population.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crossbeam_queue::SegQueue;
+
+use crate::error::TesError;
+use crate::field::TraceField;
+use crate::grid::{DensityGrid, RegimeThresholds};
+use crate::isotope::{IsotopeGrid, ServiceColor};
+use crate::relation::Relation;
+use crate::shape::Shape;
+use crate::space::Space;
+use crate::substrate::advance_shapes;
+
+/// A substrate that admits shapes from many threads concurrently.
+///
+/// # Admission
+///
+/// A shape's first contribution is deposited at admission, as part of the
+/// same atomic habitability check. This counts as the first tick of its
+/// lifetime, so a shape still contributes exactly `lifetime` times.
+///
+/// Relationality is unconditional (A0 relations are not evaluated) and
+/// footprints are single-cell.
+pub struct ConcurrentSubstrate<P = (), F = DensityGrid> {
+    /// The topographic space
+    space: Space<F>,
+    /// Shapes admitted since the last tick (lock-free, multi-producer)
+    arrivals: SegQueue<Shape<P>>,
+    /// Living shapes, only touched by the ticker
+    shapes: Mutex<Vec<Shape<P>>>,
+    /// Current tick count
+    tick_count: AtomicU64,
+    /// Next shape ID
+    next_id: AtomicU64,
+}
+
+impl<P> ConcurrentSubstrate<P> {
+    /// Create a new concurrent substrate.
+    pub fn new(width: usize, height: usize, decay_rate: u32, threshold: u32) -> Self {
+        Self::from_space(Space::new(width, height, decay_rate, threshold))
+    }
+
+    /// Create a validated concurrent substrate (see [`Space::try_new`]).
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        Space::try_new(width, height, decay_rate, thresholds).map(Self::from_space)
+    }
+}
+
+impl<P> ConcurrentSubstrate<P, IsotopeGrid> {
+    /// Create a new concurrent substrate running on an isotope (RGB) field.
+    pub fn with_isotopes(width: usize, height: usize, decay_rate: u32, threshold: u32) -> Self {
+        Self::from_space(Space::with_isotopes(width, height, decay_rate, threshold))
+    }
+
+    /// Create a validated isotope concurrent substrate (see [`Space::try_new`]).
+    pub fn try_with_isotopes(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        Space::try_with_isotopes(width, height, decay_rate, thresholds).map(Self::from_space)
+    }
+}
+
+impl<P, F: TraceField> ConcurrentSubstrate<P, F> {
+    /// Create a concurrent substrate over an existing space.
+    pub fn from_space(space: Space<F>) -> Self {
+        Self {
+            space,
+            arrivals: SegQueue::new(),
+            shapes: Mutex::new(Vec::new()),
+            tick_count: AtomicU64::new(0),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Spawn a new shape hosting `payload` at position.
+    ///
+    /// Safe to call from any number of threads. Returns the shape ID, or
+    /// hands the payload back if position is not habitable.
+    pub fn spawn_with(
+        &self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+        payload: P,
+    ) -> Result<u64, P> {
+        self.spawn_colored_with(
+            x,
+            y,
+            lifetime,
+            contribution,
+            ServiceColor::neutral(),
+            payload,
+        )
+    }
+
+    /// Spawn a new shape contributing trace in `color`.
+    pub fn spawn_colored_with(
+        &self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+        color: ServiceColor,
+        payload: P,
+    ) -> Result<u64, P> {
+        // A1: Atomic habitability check + first contribution (A3).
+        // A shape born dead (lifetime 0) leaves no trace.
+        let deposit = if lifetime > 0 { contribution } else { 0 };
+        if !self.space.try_contribute(x, y, deposit, color) {
+            return Err(payload);
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let remaining = lifetime.saturating_sub(1);
+        let mut shape = Shape::new(id, x, y, 100, remaining, 1.0, contribution);
+        shape.color = color;
+        shape.attach_payload(payload);
+
+        self.arrivals.push(shape);
+        Ok(id)
+    }
+
+    /// Run one tick of the simulation.
+    ///
+    /// Merges shapes admitted since the last tick, then runs the same
+    /// lifecycle as [`Substrate::tick`](crate::Substrate::tick).
+    /// Meant to be called from a single ticker thread; concurrent callers
+    /// are serialized.
+    pub fn tick(&self) {
+        self.tick_with(|_, _| {});
+    }
+
+    /// Run one tick, handing payloads of expired shapes to `on_expire`.
+    pub fn tick_with<E>(&self, mut on_expire: E)
+    where
+        E: FnMut(u64, P),
+    {
+        let mut shapes = self.shapes.lock().unwrap_or_else(|e| e.into_inner());
+        self.tick_count.fetch_add(1, Ordering::Relaxed);
+
+        // Tick boundary: merge arrivals
+        while let Some(shape) = self.arrivals.pop() {
+            shapes.push(shape);
+        }
+
+        // Phases 0-3: shape lifecycle
+        advance_shapes(
+            &self.space,
+            &mut shapes,
+            Relation::Unconditional,
+            &mut Vec::new(),
+            &mut |id, payload| {
+                if let Some(payload) = payload {
+                    on_expire(id, payload);
+                }
+            },
+        );
+
+        // Phase 4: Space decay (A4)
+        self.space.tick();
+    }
+
+    /// Run multiple ticks.
+    pub fn run(&self, ticks: u64) {
+        for _ in 0..ticks {
+            self.tick();
+        }
+    }
+
+    /// Get current tick count.
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count.load(Ordering::Relaxed)
+    }
+
+    /// Get number of living shapes, including those awaiting the next tick.
+    pub fn shape_count(&self) -> usize {
+        let living = self.shapes.lock().unwrap_or_else(|e| e.into_inner()).len();
+        living + self.arrivals.len()
+    }
+
+    /// Get reference to space.
+    pub fn space(&self) -> &Space<F> {
+        &self.space
+    }
+}
+
+impl<F: TraceField> ConcurrentSubstrate<(), F> {
+    /// Spawn a new shape at position.
+    ///
+    /// Returns the shape ID, or None if position is not habitable.
+    pub fn spawn(&self, x: usize, y: usize, lifetime: u32, contribution: u32) -> Option<u64> {
+        self.spawn_with(x, y, lifetime, contribution, ()).ok()
+    }
+
+    /// Spawn a new shape contributing trace in `color`.
+    ///
+    /// Returns the shape ID, or None if position is not habitable.
+    pub fn spawn_colored(
+        &self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+        color: ServiceColor,
+    ) -> Option<u64> {
+        self.spawn_colored_with(x, y, lifetime, contribution, color, ())
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_lifecycle_matches_substrate() {
+        let sub = ConcurrentSubstrate::new(10, 10, 5, 100);
+
+        assert!(sub.spawn(5, 5, 20, 10).is_some());
+        assert_eq!(sub.shape_count(), 1);
+
+        // Admission deposit is the first of 10 contributions
+        sub.run(9);
+        assert_eq!(sub.space().density(5, 5), 10 * 10 - 9 * 5);
+    }
+
+    #[test]
+    fn test_payload_expires_after_lifetime() {
+        let sub = ConcurrentSubstrate::new(10, 10, 0, 1000);
+        let id = sub.spawn_with(1, 1, 3, 10, "job").unwrap();
+
+        let mut expired = Vec::new();
+        for _ in 0..3 {
+            sub.tick_with(|id, payload| expired.push((id, payload)));
+        }
+
+        assert_eq!(expired, vec![(id, "job")]);
+        assert_eq!(sub.space().density(1, 1), 30);
+        assert_eq!(sub.shape_count(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_spawn_never_overshoots() {
+        // No decay, no ticks: admissions alone fill the cell.
+        let sub = Arc::new(ConcurrentSubstrate::<()>::new(4, 4, 0, 1000));
+        let accepted = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let sub = Arc::clone(&sub);
+                let accepted = Arc::clone(&accepted);
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        if sub.spawn(2, 2, 10, 7).is_some() {
+                            accepted.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        // Exactly ceil(1000 / 7) admissions; a racy check would admit more
+        assert_eq!(accepted.load(Ordering::Relaxed), 143);
+        assert_eq!(sub.space().density(2, 2), 143 * 7);
+        assert_eq!(sub.shape_count(), 143);
+    }
+
+    #[test]
+    fn test_a1_holds_under_concurrent_ticking() {
+        const THRESHOLD: u32 = 1000;
+        const CONTRIBUTION: u32 = 7;
+
+        // Lifetime 1: all presence happens at admission, ticks only decay.
+        let sub = Arc::new(ConcurrentSubstrate::<u64>::new(4, 4, 5, THRESHOLD));
+        let done = Arc::new(AtomicBool::new(false));
+
+        let ticker = {
+            let sub = Arc::clone(&sub);
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let mut max_seen = 0;
+                while !done.load(Ordering::Relaxed) {
+                    sub.tick();
+                    for (x, y) in [(0, 0), (1, 1), (2, 2), (3, 3)] {
+                        max_seen = max_seen.max(sub.space().density(x, y));
+                    }
+                }
+                max_seen
+            })
+        };
+
+        let spawners: Vec<_> = (0..8u64)
+            .map(|t| {
+                let sub = Arc::clone(&sub);
+                thread::spawn(move || {
+                    for i in 0..20_000u64 {
+                        let c = ((t + i) % 4) as usize;
+                        let _ = sub.spawn_with(c, c, 1, CONTRIBUTION, i);
+                    }
+                })
+            })
+            .collect();
+        for h in spawners {
+            h.join().unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        let max_seen = ticker.join().unwrap();
+
+        // Every admission saw density < threshold before its deposit
+        assert!(
+            max_seen < THRESHOLD + CONTRIBUTION,
+            "max density {}",
+            max_seen
+        );
+        for c in 0..4 {
+            assert!(sub.space().density(c, c) < THRESHOLD + CONTRIBUTION);
+        }
+    }
+}
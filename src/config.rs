@@ -0,0 +1,697 @@
+//! Config - declarative description of a space
+//!
+//! Parameters are given as floats on the density scale (1.0 = one unit of
+//! trace) and converted to the fixed-point grid representation (1000 = 1.0)
+//! when the space is built, so the resolution is 0.001.
+//!
+//! Three equivalent front ends:
+//!
+//! ```
+//! use tes::{space, Space};
+//!
+//! // Builder
+//! let a = Space::builder()
+//!     .dimensions(64, 64)
+//!     .decay_rate(0.05)
+//!     .regimes(0.9, 0.4)
+//!     .build()
+//!     .unwrap();
+//!
+//! // Macro (docs/gui.md §5.1)
+//! let b = space! {
+//!     dimensions: [64, 64],
+//!     decay_rate: 0.05,
+//!     regimes: {
+//!         solid: 0.9,
+//!         liquid: 0.4,
+//!     }
+//! }
+//! .unwrap();
+//!
+//! assert_eq!(a.field().regime_thresholds(), b.field().regime_thresholds());
+//! ```
+//!
+//! and, with the `toml` feature, [`SpaceConfig::from_toml`].
+
+use std::fmt;
+
+use crate::error::TesError;
+use crate::grid::{check_grid, check_threshold, DecayModel, DensityGrid, RegimeThresholds};
+use crate::isotope::IsotopeGrid;
+use crate::space::Space;
+use crate::topology::{Boundary, Topology};
+
+/// Regime boundaries on the density scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct RegimeConfig {
+    /// Density at which a cell becomes Solid
+    pub solid: f32,
+    /// Density at which a cell becomes Liquid (at most `solid`)
+    pub liquid: f32,
+    /// Hysteresis margin: a cell leaves a regime this far below its entry
+    pub hysteresis: f32,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            solid: 1.0,
+            liquid: 0.5,
+            hysteresis: 0.0,
+        }
+    }
+}
+
+/// Complete description of a space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct SpaceConfig {
+    /// Grid size `[width, height]`
+    pub dimensions: [usize; 2],
+    /// Decay per tick: density units (linear) or fraction (exponential)
+    pub decay_rate: f32,
+    /// How decay is applied
+    pub decay_model: DecayModel,
+    /// Regime boundaries
+    pub regimes: RegimeConfig,
+    /// Habitability threshold (defaults to `regimes.solid`)
+    pub threshold: Option<f32>,
+    /// Lattice shape
+    pub topology: Topology,
+    /// Edge behavior of a bounded plane
+    pub boundary: Boundary,
+}
+
+impl Default for SpaceConfig {
+    fn default() -> Self {
+        Self {
+            dimensions: [100, 100],
+            decay_rate: 0.005,
+            decay_model: DecayModel::default(),
+            regimes: RegimeConfig::default(),
+            threshold: None,
+            topology: Topology::default(),
+            boundary: Boundary::default(),
+        }
+    }
+}
+
+/// Invalid space configuration.
+///
+/// Grid rules are those of the validated constructors (see [`TesError`]),
+/// reported with the configured float values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// Width or height is zero
+    ZeroDimension,
+    /// Grid too large to allocate or snapshot
+    TooLarge { width: usize, height: usize },
+    /// Parameter is negative, not finite or beyond the fixed-point range
+    InvalidValue { param: &'static str, value: f32 },
+    /// Liquid boundary is above the solid boundary
+    InvertedRegimes { solid: f32, liquid: f32 },
+    /// Hysteresis would let the Solid exit fall below the Liquid entry
+    HysteresisTooWide { hysteresis: f32, gap: f32 },
+    /// Linear decay removes more per tick than the Solid boundary
+    DecayExceedsThreshold { decay_rate: f32, threshold: f32 },
+    /// Exponential decay removes more than the whole density per tick
+    DecayAboveOne(f32),
+    /// Habitability threshold is zero or above the solid boundary
+    ThresholdOutOfRange { threshold: f32, solid: f32 },
+    /// Configuration text could not be parsed
+    Parse(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::ZeroDimension => write!(f, "dimensions must be non-zero"),
+            ConfigError::TooLarge { width, height } => {
+                write!(f, "{}x{} grid is too large", width, height)
+            }
+            ConfigError::InvalidValue { param, value } => write!(
+                f,
+                "{} must be a finite, non-negative density below {}, got {}",
+                param,
+                u32::MAX / 1000,
+                value
+            ),
+            ConfigError::InvertedRegimes { solid, liquid } => write!(
+                f,
+                "liquid boundary {} must not exceed solid boundary {}",
+                liquid, solid
+            ),
+            ConfigError::HysteresisTooWide { hysteresis, gap } => write!(
+                f,
+                "hysteresis {} exceeds the solid-liquid gap {}",
+                hysteresis, gap
+            ),
+            ConfigError::DecayExceedsThreshold {
+                decay_rate,
+                threshold,
+            } => write!(
+                f,
+                "linear decay rate {} exceeds solid boundary {}",
+                decay_rate, threshold
+            ),
+            ConfigError::DecayAboveOne(rate) => {
+                write!(f, "exponential decay rate {} exceeds 1.0", rate)
+            }
+            ConfigError::ThresholdOutOfRange { threshold, solid } => write!(
+                f,
+                "threshold {} must be positive and not exceed solid boundary {}",
+                threshold, solid
+            ),
+            ConfigError::Parse(msg) => write!(f, "invalid configuration: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Convert a density-scale float to fixed-point.
+fn fixed(param: &'static str, value: f32) -> Result<u32, ConfigError> {
+    let scaled = (value as f64 * 1000.0).round();
+    if !value.is_finite() || value < 0.0 || scaled > u32::MAX as f64 {
+        return Err(ConfigError::InvalidValue { param, value });
+    }
+    Ok(scaled as u32)
+}
+
+/// Fixed-point parameters of a validated config.
+struct Resolved {
+    width: usize,
+    height: usize,
+    decay_rate: u32,
+    thresholds: RegimeThresholds,
+    threshold: u32,
+}
+
+impl SpaceConfig {
+    /// Parse a TOML document (feature `toml`).
+    ///
+    /// Keys mirror the struct fields; missing keys take their defaults.
+    ///
+    /// ```toml
+    /// dimensions = [1024, 1024]
+    /// decay_rate = 0.05
+    /// decay_model = "exponential"
+    /// topology = "torus"
+    ///
+    /// [regimes]
+    /// solid = 0.9
+    /// liquid = 0.4
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(text).map_err(|e| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the configuration without building a space.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.resolve().map(|_| ())
+    }
+
+    /// Habitability threshold (explicit or `regimes.solid`).
+    pub fn threshold(&self) -> f32 {
+        self.threshold.unwrap_or(self.regimes.solid)
+    }
+
+    /// Build a space over a monochrome density field.
+    pub fn build(&self) -> Result<Space, ConfigError> {
+        let r = self.resolve()?;
+        let mut grid = DensityGrid::with_regimes(r.width, r.height, r.decay_rate, r.thresholds);
+        grid.set_decay_model(self.decay_model);
+        grid.set_topology(self.topology, self.boundary);
+        Ok(Space::from_field(grid, r.threshold))
+    }
+
+    /// Build a space over an isotope (RGB) field.
+    pub fn build_isotopes(&self) -> Result<Space<IsotopeGrid>, ConfigError> {
+        let r = self.resolve()?;
+        let mut grid = IsotopeGrid::with_regimes(r.width, r.height, r.decay_rate, r.thresholds);
+        grid.set_decay_model(self.decay_model);
+        grid.set_topology(self.topology, self.boundary);
+        Ok(Space::from_field(grid, r.threshold))
+    }
+
+    fn resolve(&self) -> Result<Resolved, ConfigError> {
+        let [width, height] = self.dimensions;
+        let RegimeConfig {
+            solid,
+            liquid,
+            hysteresis,
+        } = self.regimes;
+        let decay_rate = fixed("decay_rate", self.decay_rate)?;
+        let solid_fixed = fixed("regimes.solid", solid)?;
+        let liquid_fixed = fixed("regimes.liquid", liquid)?;
+        let margin = fixed("regimes.hysteresis", hysteresis)?;
+        let threshold = fixed("threshold", self.threshold())?;
+
+        let thresholds = RegimeThresholds::with_margin(solid_fixed, liquid_fixed, margin);
+
+        // Grid rules live in `check_grid`; report them in config terms
+        let config_error = |e| match e {
+            TesError::ZeroDimension => ConfigError::ZeroDimension,
+            TesError::TooLarge { width, height } => ConfigError::TooLarge { width, height },
+            TesError::InvertedThresholds(_) if liquid_fixed > solid_fixed => {
+                ConfigError::InvertedRegimes { solid, liquid }
+            }
+            TesError::InvertedThresholds(_) => ConfigError::HysteresisTooWide {
+                hysteresis,
+                gap: solid - liquid,
+            },
+            TesError::DecayExceedsThreshold { .. } => ConfigError::DecayExceedsThreshold {
+                decay_rate: self.decay_rate,
+                threshold: solid,
+            },
+            TesError::DecayAboveOne(_) => ConfigError::DecayAboveOne(self.decay_rate),
+            TesError::ThresholdOutOfRange { .. } => ConfigError::ThresholdOutOfRange {
+                threshold: self.threshold(),
+                solid,
+            },
+            TesError::Config(e) => e,
+            e @ (TesError::EmptySlit
+            | TesError::InvalidSegment(_)
+            | TesError::Snapshot(_)
+            | TesError::Io(_)) => ConfigError::Parse(e.to_string()),
+        };
+        check_grid(width, height, self.decay_model, decay_rate, thresholds)
+            .and_then(|()| check_threshold(threshold, thresholds))
+            .map_err(config_error)?;
+
+        Ok(Resolved {
+            width,
+            height,
+            decay_rate,
+            thresholds,
+            threshold,
+        })
+    }
+}
+
+/// Chained construction of a [`SpaceConfig`].
+///
+/// Created by [`Space::builder`]; unset parameters keep their defaults.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpaceBuilder {
+    config: SpaceConfig,
+}
+
+impl SpaceBuilder {
+    /// Set grid size.
+    pub fn dimensions(mut self, width: usize, height: usize) -> Self {
+        self.config.dimensions = [width, height];
+        self
+    }
+
+    /// Set decay per tick.
+    pub fn decay_rate(mut self, rate: f32) -> Self {
+        self.config.decay_rate = rate;
+        self
+    }
+
+    /// Set how decay is applied.
+    pub fn decay_model(mut self, model: DecayModel) -> Self {
+        self.config.decay_model = model;
+        self
+    }
+
+    /// Set Solid and Liquid entry boundaries.
+    pub fn regimes(mut self, solid: f32, liquid: f32) -> Self {
+        self.config.regimes.solid = solid;
+        self.config.regimes.liquid = liquid;
+        self
+    }
+
+    /// Set regime hysteresis margin.
+    pub fn hysteresis(mut self, margin: f32) -> Self {
+        self.config.regimes.hysteresis = margin;
+        self
+    }
+
+    /// Set habitability threshold (default: Solid boundary).
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.config.threshold = Some(threshold);
+        self
+    }
+
+    /// Set lattice shape.
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.config.topology = topology;
+        self
+    }
+
+    /// Set edge behavior of a bounded plane.
+    pub fn boundary(mut self, boundary: Boundary) -> Self {
+        self.config.boundary = boundary;
+        self
+    }
+
+    /// Get the collected configuration.
+    pub fn config(&self) -> SpaceConfig {
+        self.config
+    }
+
+    /// Build a space over a monochrome density field.
+    pub fn build(&self) -> Result<Space, ConfigError> {
+        self.config.build()
+    }
+
+    /// Build a space over an isotope (RGB) field.
+    pub fn build_isotopes(&self) -> Result<Space<IsotopeGrid>, ConfigError> {
+        self.config.build_isotopes()
+    }
+}
+
+impl Space {
+    /// Start a declarative space description.
+    pub fn builder() -> SpaceBuilder {
+        SpaceBuilder::default()
+    }
+}
+
+/// Build a [`Space`] from a declarative block (docs/gui.md §5.1).
+///
+/// Keys are the fields of [`SpaceConfig`]; `decay_model`, `topology` and
+/// `boundary` take a bare variant name. Evaluates to
+/// `Result<Space, ConfigError>`.
+///
+/// ```
+/// let space = tes::space! {
+///     dimensions: [32, 16],
+///     decay_rate: 0.05,
+///     decay_model: Exponential,
+///     topology: Torus,
+///     regimes: { solid: 0.9, liquid: 0.4, hysteresis: 0.05 },
+/// }
+/// .unwrap();
+/// assert_eq!(space.dimensions(), (32, 16));
+/// ```
+#[macro_export]
+macro_rules! space {
+    ($($body:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut config = $crate::SpaceConfig::default();
+        $crate::__space_fields!(config; $($body)*);
+        config.build()
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __space_fields {
+    ($c:ident;) => {};
+    ($c:ident; dimensions: [$w:expr, $h:expr] $(, $($rest:tt)*)?) => {
+        $c.dimensions = [$w, $h];
+        $crate::__space_fields!($c; $($($rest)*)?);
+    };
+    ($c:ident; regimes: { $($key:ident: $v:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $($c.regimes.$key = $v;)*
+        $crate::__space_fields!($c; $($($rest)*)?);
+    };
+    ($c:ident; decay_model: $v:ident $(, $($rest:tt)*)?) => {
+        $c.decay_model = $crate::DecayModel::$v;
+        $crate::__space_fields!($c; $($($rest)*)?);
+    };
+    ($c:ident; topology: $v:ident $(, $($rest:tt)*)?) => {
+        $c.topology = $crate::Topology::$v;
+        $crate::__space_fields!($c; $($($rest)*)?);
+    };
+    ($c:ident; boundary: $v:ident $(, $($rest:tt)*)?) => {
+        $c.boundary = $crate::Boundary::$v;
+        $crate::__space_fields!($c; $($($rest)*)?);
+    };
+    ($c:ident; threshold: $v:expr $(, $($rest:tt)*)?) => {
+        $c.threshold = Some($v);
+        $crate::__space_fields!($c; $($($rest)*)?);
+    };
+    ($c:ident; $key:ident: $v:expr $(, $($rest:tt)*)?) => {
+        $c.$key = $v;
+        $crate::__space_fields!($c; $($($rest)*)?);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::footprint::Footprint;
+    use crate::grid::Regime;
+    use crate::isotope::ServiceColor;
+
+    #[test]
+    fn test_builder_converts_to_fixed_point() {
+        let space = Space::builder()
+            .dimensions(8, 4)
+            .decay_rate(0.05)
+            .regimes(0.9, 0.4)
+            .hysteresis(0.1)
+            .build()
+            .unwrap();
+
+        assert_eq!(space.dimensions(), (8, 4));
+        assert_eq!(space.threshold(), 900);
+        assert_eq!(space.field().decay_rate(), 50);
+        assert_eq!(
+            space.field().regime_thresholds(),
+            RegimeThresholds::with_margin(900, 400, 100)
+        );
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let err = |b: SpaceBuilder| b.build().err().unwrap();
+
+        assert_eq!(
+            err(Space::builder().dimensions(0, 4)),
+            ConfigError::ZeroDimension
+        );
+        assert!(matches!(
+            err(Space::builder().dimensions(usize::MAX, 2)),
+            ConfigError::TooLarge { .. }
+        ));
+        assert!(matches!(
+            err(Space::builder().decay_rate(f32::NAN)),
+            ConfigError::InvalidValue {
+                param: "decay_rate",
+                ..
+            }
+        ));
+        assert!(matches!(
+            err(Space::builder().regimes(-1.0, 0.5)),
+            ConfigError::InvalidValue {
+                param: "regimes.solid",
+                ..
+            }
+        ));
+        assert_eq!(
+            err(Space::builder().regimes(0.4, 0.9)),
+            ConfigError::InvertedRegimes {
+                solid: 0.4,
+                liquid: 0.9
+            }
+        );
+        assert!(matches!(
+            err(Space::builder().regimes(1.0, 0.5).hysteresis(0.6)),
+            ConfigError::HysteresisTooWide { .. }
+        ));
+        assert!(matches!(
+            err(Space::builder().decay_rate(2.0)),
+            ConfigError::DecayExceedsThreshold { .. }
+        ));
+        assert_eq!(
+            err(Space::builder()
+                .decay_model(DecayModel::Exponential)
+                .decay_rate(1.5)),
+            ConfigError::DecayAboveOne(1.5)
+        );
+
+        let msg = err(Space::builder().regimes(0.4, 0.9)).to_string();
+        assert!(msg.contains("liquid boundary 0.9 must not exceed solid boundary 0.4"));
+    }
+
+    #[test]
+    fn test_rules_match_constructors() {
+        // Same rules as `check_grid`: coinciding boundaries are valid...
+        let space = Space::builder().regimes(0.8, 0.8).build().unwrap();
+        let t = space.field().regime_thresholds();
+        assert!(DensityGrid::try_with_regimes(4, 4, 5, t).is_ok());
+
+        // ...and linear decay is bounded by the Solid boundary
+        let config = Space::builder()
+            .regimes(0.9, 0.4)
+            .threshold(0.5)
+            .decay_rate(0.95);
+        assert_eq!(
+            config.build().err(),
+            Some(ConfigError::DecayExceedsThreshold {
+                decay_rate: 0.95,
+                threshold: 0.9
+            })
+        );
+        assert!(config.decay_rate(0.6).build().is_ok());
+
+        // The threshold admits below the Solid boundary, never into it
+        for threshold in [0.0, 0.95] {
+            assert_eq!(
+                config.decay_rate(0.6).threshold(threshold).build().err(),
+                Some(ConfigError::ThresholdOutOfRange {
+                    threshold,
+                    solid: 0.9
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_macro_matches_builder() {
+        let space = crate::space! {
+            dimensions: [16, 8],
+            decay_rate: 0.02,
+            decay_model: Exponential,
+            boundary: Open,
+            threshold: 0.8,
+            regimes: {
+                solid: 0.9,
+                liquid: 0.4,
+            }
+        }
+        .unwrap();
+
+        let expected = Space::builder()
+            .dimensions(16, 8)
+            .decay_rate(0.02)
+            .decay_model(DecayModel::Exponential)
+            .boundary(Boundary::Open)
+            .threshold(0.8)
+            .regimes(0.9, 0.4)
+            .build()
+            .unwrap();
+
+        assert_eq!(space.dimensions(), expected.dimensions());
+        assert_eq!(space.threshold(), 800);
+        assert_eq!(space.field().decay_model(), DecayModel::Exponential);
+        assert_eq!(space.field().boundary(), Boundary::Open);
+        assert_eq!(
+            space.field().regime_thresholds(),
+            expected.field().regime_thresholds()
+        );
+
+        assert_eq!(
+            crate::space! { dimensions: [0, 8] }.err(),
+            Some(ConfigError::ZeroDimension)
+        );
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        let space = Space::builder()
+            .dimensions(3, 3)
+            .decay_model(DecayModel::Exponential)
+            .decay_rate(0.5)
+            .build()
+            .unwrap();
+        space.contribute(1, 1, 1000);
+
+        space.tick();
+        assert_eq!(space.density(1, 1), 500);
+        space.tick();
+        assert_eq!(space.density(1, 1), 250);
+
+        // Rounding up still drains the cell
+        for _ in 0..10 {
+            space.tick();
+        }
+        assert_eq!(space.density(1, 1), 0);
+    }
+
+    #[test]
+    fn test_torus_diffusion_wraps() {
+        let space = Space::builder()
+            .dimensions(4, 4)
+            .decay_rate(0.0)
+            .topology(Topology::Torus)
+            .build_isotopes()
+            .unwrap();
+        space.contribute(0, 0, 1000);
+        space.diffuse();
+
+        // Corner leaks to the opposite edges, nothing is lost
+        assert!(space.density(3, 0) > 0);
+        assert!(space.density(0, 3) > 0);
+        assert_eq!(space.stats().total_mass, 1000);
+    }
+
+    #[test]
+    fn test_open_boundary_loses_mass() {
+        let closed = Space::builder().dimensions(3, 3).decay_rate(0.0);
+        let open = closed.boundary(Boundary::Open).build().unwrap();
+        let closed = closed.build().unwrap();
+
+        for space in [&closed, &open] {
+            space.contribute(0, 1, 800);
+            space.diffuse();
+        }
+        assert_eq!(closed.stats().total_mass, 800);
+        assert!(open.stats().total_mass < 800);
+    }
+
+    #[test]
+    fn test_footprint_wraps_on_torus() {
+        let space = Space::builder()
+            .dimensions(5, 5)
+            .decay_rate(0.0)
+            .regimes(0.9, 0.4)
+            .topology(Topology::Torus)
+            .build()
+            .unwrap();
+        let disk = Footprint::Disk { radius: 1 };
+        space.contribute_footprint(0, 0, 500, &disk, ServiceColor::neutral());
+
+        assert!(space.density(4, 0) > 0);
+        assert!(space.density(0, 4) > 0);
+        assert_eq!(space.stats().total_mass, 500);
+        assert_eq!(space.field().topology(), Topology::Torus);
+        assert_eq!(space.regime(4, 0), Regime::Gas);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_config() {
+        let config = SpaceConfig::from_toml(
+            r#"
+            dimensions = [64, 32]
+            decay_rate = 0.05
+            decay_model = "exponential"
+            topology = "torus"
+
+            [regimes]
+            solid = 0.9
+            liquid = 0.4
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.dimensions, [64, 32]);
+        assert_eq!(config.decay_model, DecayModel::Exponential);
+        assert_eq!(config.topology, Topology::Torus);
+        assert_eq!(config.boundary, Boundary::Closed);
+        assert_eq!(config.build().unwrap().threshold(), 900);
+
+        let round_trip = SpaceConfig::from_toml(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(round_trip, config);
+
+        assert!(matches!(
+            SpaceConfig::from_toml("dimension = [1, 1]"),
+            Err(ConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            SpaceConfig::from_toml("[regimes]\nsolid = 0.2\nliquid = 0.4"),
+            Err(ConfigError::InvertedRegimes { .. })
+        ));
+    }
+}
@@ -0,0 +1,361 @@
+//! Tick driver - wall-clock driven decay
+//!
+//! `Substrate::run(ticks)` spends time synchronously. Services that use TES
+//! as admission control need decay to progress on its own: the driver
+//! advances a target at a fixed period on a background thread.
+//!
+//! Time is read from a `Clock`, so tests can drive it by hand.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::concurrent::ConcurrentSubstrate;
+use crate::field::TraceField;
+use crate::grid::DensityGrid;
+use crate::isotope::IsotopeGrid;
+use crate::space::Space;
+use crate::substrate::Substrate;
+
+/// Something that can be advanced by one tick through a shared reference.
+pub trait Tick {
+    /// Advance by one tick.
+    fn tick(&self);
+}
+
+impl Tick for DensityGrid {
+    fn tick(&self) {
+        self.apply_decay();
+    }
+}
+
+impl Tick for IsotopeGrid {
+    fn tick(&self) {
+        self.apply_decay();
+    }
+}
+
+impl<F: TraceField> Tick for Space<F> {
+    fn tick(&self) {
+        Space::tick(self);
+    }
+}
+
+impl<P, F: TraceField> Tick for ConcurrentSubstrate<P, F> {
+    fn tick(&self) {
+        ConcurrentSubstrate::tick(self);
+    }
+}
+
+impl<P, F: TraceField> Tick for Mutex<Substrate<P, F>> {
+    fn tick(&self) {
+        self.lock().unwrap_or_else(|e| e.into_inner()).tick();
+    }
+}
+
+/// Source of elapsed time.
+pub trait Clock {
+    /// Time elapsed since the clock's origin.
+    fn now(&self) -> Duration;
+}
+
+/// Monotonic wall clock.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    /// Create a clock whose origin is now.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// Virtual clock advanced by hand (for tests and simulations).
+///
+/// Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Create a clock at time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move time forward.
+    pub fn advance(&self, by: Duration) {
+        self.nanos
+            .fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+}
+
+/// Advances a target at a configured period.
+///
+/// Call [`poll`](TickDriver::poll) from your own loop, or
+/// [`spawn`](TickDriver::spawn) a background thread.
+///
+/// # Catch-up
+///
+/// If ticks were missed (slow target, suspended process), the next poll
+/// runs them back to back, up to `max_catch_up`. Anything beyond that is
+/// skipped: decay is a projection, not a ledger.
+pub struct TickDriver<T, C = SystemClock> {
+    target: Arc<T>,
+    clock: C,
+    period: Duration,
+    max_catch_up: u64,
+    /// Ticks accounted for since start (run + skipped)
+    elapsed_ticks: u64,
+    ticks: u64,
+    skipped: u64,
+}
+
+impl<T: Tick> TickDriver<T> {
+    /// Create a driver on the system clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn new(target: Arc<T>, period: Duration) -> Self {
+        Self::with_clock(target, period, SystemClock::new())
+    }
+}
+
+impl<T: Tick, C: Clock> TickDriver<T, C> {
+    /// Create a driver on a custom clock.
+    ///
+    /// Ticks are aligned to multiples of `period` on the clock; ticks
+    /// already due when the driver is created are not run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn with_clock(target: Arc<T>, period: Duration, clock: C) -> Self {
+        assert!(!period.is_zero(), "tick period must be non-zero");
+        let elapsed_ticks = Self::ticks_at(clock.now(), period);
+        Self {
+            target,
+            clock,
+            period,
+            max_catch_up: 100,
+            elapsed_ticks,
+            ticks: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Set how many missed ticks are run back to back (default 100).
+    pub fn set_max_catch_up(&mut self, max_catch_up: u64) {
+        self.max_catch_up = max_catch_up;
+    }
+
+    /// Run every tick that is due by now.
+    ///
+    /// Returns the number of ticks run.
+    pub fn poll(&mut self) -> u64 {
+        let due = Self::ticks_at(self.clock.now(), self.period);
+        let behind = due.saturating_sub(self.elapsed_ticks);
+        let run = behind.min(self.max_catch_up);
+
+        for _ in 0..run {
+            self.target.tick();
+        }
+
+        self.ticks += run;
+        self.skipped += behind - run;
+        self.elapsed_ticks += behind;
+        run
+    }
+
+    /// Time until the next tick is due.
+    pub fn until_next(&self) -> Duration {
+        let next = self.period.as_nanos() * (self.elapsed_ticks as u128 + 1);
+        Duration::from_nanos(next.min(u64::MAX as u128) as u64).saturating_sub(self.clock.now())
+    }
+
+    /// Ticks run so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Ticks skipped because catch-up was exhausted.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Get the driven target.
+    pub fn target(&self) -> &Arc<T> {
+        &self.target
+    }
+
+    fn ticks_at(now: Duration, period: Duration) -> u64 {
+        (now.as_nanos() / period.as_nanos()) as u64
+    }
+}
+
+impl<T, C> TickDriver<T, C>
+where
+    T: Tick + Send + Sync + 'static,
+    C: Clock + Send + 'static,
+{
+    /// Move the driver to a background thread.
+    ///
+    /// The thread sleeps until the next tick is due and stops when the
+    /// returned handle is shut down or dropped.
+    pub fn spawn(mut self) -> DriverHandle<T, C> {
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("tes-tick-driver".into())
+            .spawn(move || {
+                loop {
+                    self.poll();
+                    match stopped.recv_timeout(self.until_next()) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                self
+            })
+            .expect("failed to spawn tick driver thread");
+
+        DriverHandle {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Handle to a background tick driver.
+///
+/// Dropping the handle stops the driver and waits for its thread.
+pub struct DriverHandle<T, C = SystemClock> {
+    stop: Sender<()>,
+    thread: Option<JoinHandle<TickDriver<T, C>>>,
+}
+
+impl<T, C> DriverHandle<T, C> {
+    /// Stop the driver gracefully and get it back.
+    ///
+    /// A tick in progress is completed; no tick is interrupted.
+    pub fn shutdown(mut self) -> TickDriver<T, C> {
+        let _ = self.stop.send(());
+        let thread = self.thread.take().expect("driver thread already joined");
+        thread.join().expect("tick driver thread panicked")
+    }
+}
+
+impl<T, C> Drop for DriverHandle<T, C> {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = self.stop.send(());
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_drives_decay() {
+        let grid = Arc::new(DensityGrid::new(4, 4, 10, 1000, 500));
+        grid.contribute(1, 1, 100);
+
+        let clock = ManualClock::new();
+        let mut driver = TickDriver::with_clock(grid, Duration::from_millis(10), clock.clone());
+
+        // Nothing due yet
+        assert_eq!(driver.poll(), 0);
+
+        clock.advance(Duration::from_millis(25));
+        assert_eq!(driver.poll(), 2);
+        assert_eq!(driver.target().density(1, 1), 80);
+
+        // Already caught up
+        assert_eq!(driver.poll(), 0);
+        assert_eq!(driver.until_next(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_catch_up_is_bounded() {
+        let grid = Arc::new(DensityGrid::new(4, 4, 1, 1000, 500));
+        grid.contribute(0, 0, 100);
+
+        let clock = ManualClock::new();
+        let mut driver = TickDriver::with_clock(grid, Duration::from_millis(1), clock.clone());
+        driver.set_max_catch_up(5);
+
+        // Process was suspended for 50 ticks
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(driver.poll(), 5);
+        assert_eq!(driver.ticks(), 5);
+        assert_eq!(driver.skipped(), 45);
+        assert_eq!(driver.target().density(0, 0), 95);
+
+        // Back on schedule afterwards
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(driver.poll(), 1);
+    }
+
+    #[test]
+    fn test_drives_locked_substrate() {
+        let sub = Arc::new(Mutex::new(Substrate::new(4, 4, 0, 1000)));
+        sub.lock().unwrap().spawn(2, 2, 3, 10);
+
+        let clock = ManualClock::new();
+        let mut driver = TickDriver::with_clock(sub, Duration::from_secs(1), clock.clone());
+        clock.advance(Duration::from_secs(10));
+        driver.poll();
+
+        let sub = driver.target().lock().unwrap();
+        assert_eq!(sub.tick_count(), 10);
+        assert_eq!(sub.shape_count(), 0);
+        assert_eq!(sub.space().density(2, 2), 30);
+    }
+
+    #[test]
+    fn test_background_driver_shutdown() {
+        let sub = Arc::new(ConcurrentSubstrate::<()>::new(4, 4, 1, 1000));
+        let handle = TickDriver::new(Arc::clone(&sub), Duration::from_millis(1)).spawn();
+
+        // Spawning continues while time advances on its own
+        sub.spawn(1, 1, 1_000_000, 1);
+        thread::sleep(Duration::from_millis(30));
+
+        let driver = handle.shutdown();
+        let ticks = driver.ticks();
+        assert!(ticks > 0);
+        assert_eq!(sub.tick_count(), ticks);
+
+        // No ticks after shutdown
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(sub.tick_count(), ticks);
+    }
+}
@@ -0,0 +1,171 @@
+//! Error - the crate-wide error type
+//!
+//! Fallible constructors (`try_new`, `try_with_regimes`, ...) return
+//! [`TesError`] directly. Configuration and snapshot paths keep their own
+//! detailed errors ([`ConfigError`], [`SnapshotError`]) and convert into
+//! `TesError` with `?`, so callers mixing them need a single error type.
+
+use std::fmt;
+use std::io;
+
+use crate::config::ConfigError;
+use crate::grid::RegimeThresholds;
+use crate::snapshot::SnapshotError;
+
+/// Error returned by the crate's fallible operations.
+#[derive(Debug)]
+pub enum TesError {
+    /// Width or height is zero
+    ZeroDimension,
+    /// Grid too large to allocate or snapshot
+    TooLarge { width: usize, height: usize },
+    /// Liquid boundary above the solid boundary, an exit above its entry or
+    /// the Solid exit below the Liquid entry
+    InvertedThresholds(RegimeThresholds),
+    /// Decay removes more per tick than a habitable cell holds
+    DecayExceedsThreshold { decay_rate: u32, threshold: u32 },
+    /// Exponential decay removes more than the whole density per tick
+    DecayAboveOne(u32),
+    /// Habitability threshold is zero or above the Solid boundary
+    ThresholdOutOfRange { threshold: u32, solid: u32 },
+    /// Slit misses the field entirely
+    EmptySlit,
+    /// Shared grid segment has an invalid header or size
+    InvalidSegment(&'static str),
+    /// Invalid declarative configuration
+    Config(ConfigError),
+    /// Snapshot could not be written or restored
+    Snapshot(SnapshotError),
+    /// Underlying I/O failed
+    Io(io::Error),
+}
+
+impl fmt::Display for TesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroDimension => write!(f, "grid dimensions must be non-zero"),
+            Self::TooLarge { width, height } => {
+                write!(f, "{}x{} grid is too large", width, height)
+            }
+            Self::InvertedThresholds(t) => write!(
+                f,
+                "inverted regime thresholds: solid {}/{} (enter/exit), liquid {}/{}",
+                t.solid_enter, t.solid_exit, t.liquid_enter, t.liquid_exit
+            ),
+            Self::DecayExceedsThreshold {
+                decay_rate,
+                threshold,
+            } => write!(
+                f,
+                "decay rate {} exceeds habitability threshold {}",
+                decay_rate, threshold
+            ),
+            Self::DecayAboveOne(rate) => {
+                write!(f, "exponential decay rate {} exceeds 1000 (1.0)", rate)
+            }
+            Self::ThresholdOutOfRange { threshold, solid } => write!(
+                f,
+                "habitability threshold {} must be positive and not exceed solid boundary {}",
+                threshold, solid
+            ),
+            Self::EmptySlit => write!(f, "slit lies outside the field"),
+            Self::InvalidSegment(what) => write!(f, "invalid shared grid segment: {}", what),
+            // Details come from `source()`, so error chains print them once
+            Self::Config(_) => write!(f, "invalid configuration"),
+            Self::Snapshot(_) => write!(f, "snapshot error"),
+            Self::Io(_) => write!(f, "I/O error"),
+        }
+    }
+}
+
+impl std::error::Error for TesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Config(e) => Some(e),
+            Self::Snapshot(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for TesError {
+    fn from(e: ConfigError) -> Self {
+        Self::Config(e)
+    }
+}
+
+impl From<SnapshotError> for TesError {
+    fn from(e: SnapshotError) -> Self {
+        Self::Snapshot(e)
+    }
+}
+
+impl From<io::Error> for TesError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::DensityGrid;
+    use crate::snapshot::Snapshot;
+    use crate::space::Space;
+    use crate::substrate::Substrate;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_constructors_validate() {
+        let t = RegimeThresholds::new(1000, 500);
+
+        assert!(matches!(
+            Space::try_new(0, 10, 1, t),
+            Err(TesError::ZeroDimension)
+        ));
+        assert!(matches!(
+            Substrate::<()>::try_new(usize::MAX, 2, 1, t),
+            Err(TesError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            Space::try_with_isotopes(4, 4, 1, RegimeThresholds::new(400, 900)),
+            Err(TesError::InvertedThresholds(_))
+        ));
+        assert!(matches!(
+            Substrate::<(), _>::try_with_isotopes(4, 4, 2000, t),
+            Err(TesError::DecayExceedsThreshold {
+                decay_rate: 2000,
+                threshold: 1000
+            })
+        ));
+
+        // Liquid boundary is explicit, not threshold / 2
+        let space = Space::try_new(4, 4, 1, RegimeThresholds::new(800, 300)).unwrap();
+        assert_eq!(space.threshold(), 800);
+        assert_eq!(space.field().regime_thresholds().liquid_enter, 300);
+    }
+
+    #[test]
+    fn test_wraps_domain_errors() {
+        fn restore(bytes: &[u8]) -> Result<DensityGrid, TesError> {
+            Ok(DensityGrid::from_snapshot(bytes)?)
+        }
+        let err = restore(b"nope").err().unwrap();
+        assert!(matches!(err, TesError::Snapshot(SnapshotError::BadMagic)));
+        assert_eq!(err.to_string(), "snapshot error");
+        assert_eq!(err.source().unwrap().to_string(), "not a TES snapshot");
+
+        fn build() -> Result<Space, TesError> {
+            Ok(Space::builder().dimensions(0, 1).build()?)
+        }
+        assert!(matches!(
+            build(),
+            Err(TesError::Config(ConfigError::ZeroDimension))
+        ));
+
+        let io: TesError = io::Error::other("disk").into();
+        assert_eq!(io.to_string(), "I/O error");
+        assert_eq!(io.source().unwrap().to_string(), "disk");
+    }
+}
@@ -0,0 +1,189 @@
+//! Events - topography changes reported to observers
+//!
+//! Instead of polling `regime(x, y)` everywhere, observers registered on a
+//! `Substrate` receive the events of each tick in one batch.
+//!
+//! Events describe the field, never the shapes that caused it
+//! (Source Amnesia): a saturated cell does not say who saturated it.
+
+use crate::field::TraceField;
+use crate::grid::Regime;
+use crate::space::Space;
+
+/// A change observed during one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// A cell settled into a different regime
+    RegimeChanged {
+        x: usize,
+        y: usize,
+        from: Regime,
+        to: Regime,
+    },
+    /// A cell reached the habitability threshold (A1 now rejects)
+    CellSaturated { x: usize, y: usize },
+    /// A saturated cell became habitable again
+    CellRecovered { x: usize, y: usize },
+    /// A shape reached the end of its lifetime (A5)
+    ShapeDied { id: u64 },
+    /// A spawn was rejected as not habitable (A1)
+    SpawnRejected { x: usize, y: usize },
+}
+
+/// Receives the events of each tick.
+pub trait Observer {
+    /// Called once per tick that produced events.
+    fn on_events(&mut self, tick: u64, events: &[Event]);
+}
+
+impl<F: FnMut(u64, &[Event])> Observer for F {
+    fn on_events(&mut self, tick: u64, events: &[Event]) {
+        self(tick, events)
+    }
+}
+
+/// A value that only changes after holding a new reading for `hysteresis`
+/// consecutive observations.
+#[derive(Clone, Copy)]
+struct Debounced<T> {
+    stable: T,
+    candidate: T,
+    held: u32,
+}
+
+impl<T: Copy + PartialEq> Debounced<T> {
+    fn new(value: T) -> Self {
+        Self {
+            stable: value,
+            candidate: value,
+            held: 0,
+        }
+    }
+
+    /// Observe a raw reading. Returns `(from, to)` when the stable value flips.
+    fn observe(&mut self, raw: T, hysteresis: u32) -> Option<(T, T)> {
+        if raw == self.stable {
+            self.held = 0;
+            return None;
+        }
+        if raw != self.candidate {
+            self.candidate = raw;
+            self.held = 0;
+        }
+        self.held += 1;
+        if self.held < hysteresis {
+            return None;
+        }
+        let from = std::mem::replace(&mut self.stable, raw);
+        self.held = 0;
+        Some((from, raw))
+    }
+}
+
+/// Per-cell regime and saturation state, debounced over ticks.
+pub(crate) struct CellTracker {
+    regimes: Vec<Debounced<Regime>>,
+    saturated: Vec<Debounced<bool>>,
+    /// Ticks a new reading must hold before it is reported
+    hysteresis: u32,
+}
+
+impl CellTracker {
+    /// Start tracking from the current state of `space` (no initial events).
+    pub(crate) fn new<F: TraceField>(space: &Space<F>, hysteresis: u32) -> Self {
+        let (w, h) = space.dimensions();
+        let cells = (0..h).flat_map(|y| (0..w).map(move |x| (x, y)));
+        let (regimes, saturated) = cells
+            .map(|(x, y)| {
+                (
+                    Debounced::new(space.regime(x, y)),
+                    Debounced::new(!space.is_habitable(x, y)),
+                )
+            })
+            .unzip();
+        Self {
+            regimes,
+            saturated,
+            hysteresis,
+        }
+    }
+
+    pub(crate) fn set_hysteresis(&mut self, hysteresis: u32) {
+        self.hysteresis = hysteresis;
+    }
+
+    /// Compare every cell against its stable state.
+    pub(crate) fn scan<F: TraceField>(&mut self, space: &Space<F>, events: &mut Vec<Event>) {
+        let (w, _) = space.dimensions();
+        for (i, (regime, saturated)) in self
+            .regimes
+            .iter_mut()
+            .zip(self.saturated.iter_mut())
+            .enumerate()
+        {
+            let (x, y) = (i % w, i / w);
+
+            if let Some((from, to)) = regime.observe(space.regime(x, y), self.hysteresis) {
+                events.push(Event::RegimeChanged { x, y, from, to });
+            }
+            match saturated.observe(!space.is_habitable(x, y), self.hysteresis) {
+                Some((false, true)) => events.push(Event::CellSaturated { x, y }),
+                Some((true, false)) => events.push(Event::CellRecovered { x, y }),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounce_suppresses_flapping() {
+        let mut value = Debounced::new(Regime::Liquid);
+
+        // Alternating readings never hold long enough
+        for _ in 0..10 {
+            assert_eq!(value.observe(Regime::Solid, 3), None);
+            assert_eq!(value.observe(Regime::Liquid, 3), None);
+        }
+
+        // A sustained change is reported once
+        assert_eq!(value.observe(Regime::Solid, 3), None);
+        assert_eq!(value.observe(Regime::Solid, 3), None);
+        assert_eq!(
+            value.observe(Regime::Solid, 3),
+            Some((Regime::Liquid, Regime::Solid))
+        );
+        assert_eq!(value.observe(Regime::Solid, 3), None);
+    }
+
+    #[test]
+    fn test_scan_reports_transitions() {
+        let space = Space::new(4, 4, 0, 100);
+        let mut tracker = CellTracker::new(&space, 1);
+        let mut events = Vec::new();
+
+        space.contribute(1, 2, 120);
+        tracker.scan(&space, &mut events);
+
+        assert_eq!(
+            events,
+            vec![
+                Event::RegimeChanged {
+                    x: 1,
+                    y: 2,
+                    from: Regime::Gas,
+                    to: Regime::Solid
+                },
+                Event::CellSaturated { x: 1, y: 2 },
+            ]
+        );
+
+        events.clear();
+        tracker.scan(&space, &mut events);
+        assert!(events.is_empty(), "No events without change");
+    }
+}
@@ -0,0 +1,118 @@
+//! Trace field - the common interface of all density layouts
+//!
+//! `DensityGrid` (monochrome) and `IsotopeGrid` (RGB) store trace
+//! differently but expose the same scalar field. `Space` and `Substrate`
+//! are written once against this trait.
+
+use crate::grid::{Regime, RegimeThresholds};
+use crate::isotope::ServiceColor;
+use crate::stats::FieldStats;
+use crate::topology::Topology;
+
+/// A lock-free scalar trace field over a 2D grid.
+///
+/// All methods take `&self`: contributions and reads are atomic per cell.
+pub trait TraceField {
+    /// Get dimensions.
+    fn dimensions(&self) -> (usize, usize);
+
+    /// Contribute uncolored trace at position (side-effect).
+    fn contribute(&self, x: usize, y: usize, amount: u32);
+
+    /// Contribute trace carrying a service color signature.
+    ///
+    /// Layouts without color channels ignore the color.
+    fn contribute_colored(&self, x: usize, y: usize, amount: u32, color: ServiceColor) {
+        let _ = color;
+        self.contribute(x, y, amount);
+    }
+
+    /// Contribute uncolored trace only if position is habitable (see
+    /// [`is_habitable`](TraceField::is_habitable)).
+    ///
+    /// The threshold check and the contribution are one atomic step, so
+    /// concurrent admissions cannot race past `threshold` (A1).
+    fn try_contribute(&self, x: usize, y: usize, amount: u32, threshold: u32) -> bool;
+
+    /// Colored version of [`try_contribute`](TraceField::try_contribute).
+    fn try_contribute_colored(
+        &self,
+        x: usize,
+        y: usize,
+        amount: u32,
+        color: ServiceColor,
+        threshold: u32,
+    ) -> bool {
+        let _ = color;
+        self.try_contribute(x, y, amount, threshold)
+    }
+
+    /// Get total density at position.
+    fn density(&self, x: usize, y: usize) -> u32;
+
+    /// Get regime at position.
+    fn regime(&self, x: usize, y: usize) -> Regime;
+
+    /// Get regime boundaries.
+    fn regime_thresholds(&self) -> RegimeThresholds;
+
+    /// Get per-channel (R, G, B) density at position.
+    ///
+    /// `None` for layouts without color channels.
+    fn channels(&self, x: usize, y: usize) -> Option<(u32, u32, u32)> {
+        let _ = (x, y);
+        None
+    }
+
+    /// Get lattice topology (where footprints and diffusion land).
+    fn topology(&self) -> Topology {
+        Topology::Plane
+    }
+
+    /// Check if position is habitable (A1).
+    ///
+    /// A cell that settled Solid stays uninhabitable until it falls to the
+    /// Solid exit boundary, so admission does not flap at the threshold.
+    #[inline]
+    fn is_habitable(&self, x: usize, y: usize, threshold: u32) -> bool {
+        self.density(x, y) < threshold && self.regime(x, y) != Regime::Solid
+    }
+
+    /// Apply global decay (δ projection). Called once per tick.
+    fn apply_decay(&self);
+
+    /// Energy-conserving diffusion to 4-connected neighbors.
+    fn diffuse(&self);
+
+    /// Compute global statistics in one pass over the field.
+    fn stats(&self) -> FieldStats {
+        FieldStats::of(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::DensityGrid;
+    use crate::isotope::IsotopeGrid;
+
+    /// Written once, run against every layout.
+    fn saturate_and_recover<F: TraceField>(field: &F) {
+        assert_eq!(field.dimensions(), (10, 10));
+        assert!(field.is_habitable(4, 4, 300));
+
+        field.contribute_colored(4, 4, 300, ServiceColor::red());
+        assert_eq!(field.density(4, 4), 300);
+        assert_eq!(field.regime(4, 4), Regime::Liquid);
+        assert!(!field.is_habitable(4, 4, 300));
+
+        field.apply_decay();
+        assert!(field.is_habitable(4, 4, 300));
+    }
+
+    #[test]
+    fn test_layouts_share_semantics() {
+        saturate_and_recover(&DensityGrid::new(10, 10, 10, 1000, 200));
+        saturate_and_recover(&IsotopeGrid::new(10, 10, 10, 1000, 200));
+    }
+}
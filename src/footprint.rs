@@ -0,0 +1,260 @@
+//! Footprint - the region a shape presses on
+//!
+//! A point shape deposits its whole contribution into one cell, creating
+//! needles. A footprint spreads the same contribution over a kernel so heavy
+//! workloads raise broad hills instead.
+
+/// Spatial extent of a shape's contribution.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Footprint {
+    /// Single cell (no spreading)
+    #[default]
+    Point,
+    /// Uniform disk of given radius
+    Disk { radius: usize },
+    /// Gaussian kernel with standard deviation `sigma`, truncated at `radius`
+    ///
+    /// A `sigma` that is not positive and finite has no kernel; the
+    /// footprint behaves like `Point`.
+    Gaussian { sigma: f32, radius: usize },
+}
+
+/// How habitability is evaluated over a footprint (A1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Habitability {
+    /// Every cell under the footprint must be below threshold
+    #[default]
+    Max,
+    /// Kernel-weighted mean density must be below threshold
+    Mean,
+}
+
+impl Footprint {
+    /// Get kernel as `(dx, dy, weight)` offsets.
+    ///
+    /// Weights are normalized to sum to 1.0.
+    /// The center offset `(0, 0)` is always first.
+    pub fn kernel(&self) -> Vec<(isize, isize, f32)> {
+        if let Footprint::Gaussian { sigma, .. } = *self {
+            if !(sigma > 0.0 && sigma.is_finite()) {
+                return vec![(0, 0, 1.0)];
+            }
+        }
+        let r = isize::try_from(self.radius()).unwrap_or(isize::MAX);
+        let r_sq = (r as f32).powi(2);
+        let mut kernel = vec![(0, 0, self.weight(0.0))];
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let d_sq = (dx as f32).powi(2) + (dy as f32).powi(2);
+                if (dx, dy) != (0, 0) && d_sq <= r_sq {
+                    kernel.push((dx, dy, self.weight(d_sq)));
+                }
+            }
+        }
+
+        let total: f32 = kernel.iter().map(|&(_, _, w)| w).sum();
+        for cell in &mut kernel {
+            cell.2 /= total;
+        }
+        kernel
+    }
+
+    /// Get footprint radius (0 for a point).
+    pub fn radius(&self) -> usize {
+        match *self {
+            Footprint::Point => 0,
+            Footprint::Disk { radius } | Footprint::Gaussian { radius, .. } => radius,
+        }
+    }
+
+    /// Limit the radius to `max` (e.g. the larger grid dimension).
+    pub fn clamped(self, max: usize) -> Self {
+        match self {
+            Footprint::Disk { radius } => Footprint::Disk {
+                radius: radius.min(max),
+            },
+            Footprint::Gaussian { sigma, radius } => Footprint::Gaussian {
+                sigma,
+                radius: radius.min(max),
+            },
+            Footprint::Point => Footprint::Point,
+        }
+    }
+
+    /// Unnormalized weight at squared distance `d_sq` from the center.
+    fn weight(&self, d_sq: f32) -> f32 {
+        match *self {
+            Footprint::Gaussian { sigma, .. } => (-d_sq / (2.0 * sigma * sigma)).exp(),
+            _ => 1.0,
+        }
+    }
+
+    /// Split `amount` into integer shares over the kernel.
+    ///
+    /// Shares sum to `amount`; cells whose share is zero are left out.
+    pub(crate) fn distribute(&self, amount: u32) -> Vec<(isize, isize, u32)> {
+        apportion(&self.kernel(), amount)
+    }
+}
+
+/// Split `amount` over `kernel` by largest remainder.
+///
+/// Weights become integer units, so each cell gets the floor of its exact
+/// quota and the leftover units go to the largest remainders (the earlier
+/// cell on a tie, so the center first).
+pub(crate) fn apportion(kernel: &[(isize, isize, f32)], amount: u32) -> Vec<(isize, isize, u32)> {
+    const SCALE: f32 = (1u32 << 24) as f32;
+    let peak = kernel.iter().map(|&(_, _, w)| w).fold(0.0, f32::max);
+    if peak <= 0.0 {
+        return vec![(0, 0, amount)];
+    }
+    let units: Vec<u64> = kernel
+        .iter()
+        .map(|&(_, _, w)| (w / peak * SCALE).round() as u64)
+        .collect();
+    let total: u64 = units.iter().sum();
+
+    let mut shares = Vec::with_capacity(kernel.len());
+    let mut remainders = Vec::with_capacity(kernel.len());
+    let mut spread = 0;
+    for (&(dx, dy, _), &u) in kernel.iter().zip(&units) {
+        let quota = amount as u64 * u;
+        let share = (quota / total) as u32;
+        spread += share;
+        shares.push((dx, dy, share));
+        remainders.push(quota % total);
+    }
+
+    let mut order: Vec<usize> = (0..kernel.len()).collect();
+    order.sort_by(|&a, &b| remainders[b].cmp(&remainders[a]));
+    for &i in order.iter().take((amount - spread) as usize) {
+        shares[i].2 += 1;
+    }
+    shares.retain(|&(_, _, a)| a > 0);
+    shares
+}
+
+/// A footprint's kernel and shares, built once instead of every tick.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KernelCache {
+    footprint: Footprint,
+    amount: u32,
+    kernel: Vec<(isize, isize, f32)>,
+    shares: Vec<(isize, isize, u32)>,
+}
+
+impl KernelCache {
+    /// Build the kernel of `footprint` and its shares of `amount`.
+    pub(crate) fn new(footprint: Footprint, amount: u32) -> Self {
+        let kernel = footprint.kernel();
+        let shares = apportion(&kernel, amount);
+        Self {
+            footprint,
+            amount,
+            kernel,
+            shares,
+        }
+    }
+
+    /// Get kernel, if built for `footprint`.
+    pub(crate) fn kernel(&self, footprint: Footprint) -> Option<&[(isize, isize, f32)]> {
+        (!self.kernel.is_empty() && self.footprint == footprint).then_some(&self.kernel[..])
+    }
+
+    /// Get shares, if built for `footprint` and `amount`.
+    pub(crate) fn shares(
+        &self,
+        footprint: Footprint,
+        amount: u32,
+    ) -> Option<&[(isize, isize, u32)]> {
+        self.kernel(footprint)
+            .filter(|_| self.amount == amount)
+            .map(|_| &self.shares[..])
+    }
+}
+
+/// Offset `(x, y)` by `(dx, dy)`, or None if it leaves the positive quadrant.
+#[inline]
+pub(crate) fn offset(x: usize, y: usize, dx: isize, dy: isize) -> Option<(usize, usize)> {
+    Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_normalized() {
+        for footprint in [
+            Footprint::Point,
+            Footprint::Disk { radius: 3 },
+            Footprint::Gaussian {
+                sigma: 1.5,
+                radius: 4,
+            },
+        ] {
+            let total: f32 = footprint.kernel().iter().map(|&(_, _, w)| w).sum();
+            assert!(
+                (total - 1.0).abs() < 1e-4,
+                "{:?} sums to {}",
+                footprint,
+                total
+            );
+        }
+    }
+
+    #[test]
+    fn test_gaussian_peaks_at_center() {
+        let kernel = Footprint::Gaussian {
+            sigma: 1.0,
+            radius: 2,
+        }
+        .kernel();
+        let center = kernel[0].2;
+        assert!(kernel.iter().all(|&(_, _, w)| w <= center));
+    }
+
+    #[test]
+    fn test_degenerate_gaussian_is_a_point() {
+        for sigma in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let footprint = Footprint::Gaussian { sigma, radius: 3 };
+            assert_eq!(footprint.kernel(), [(0, 0, 1.0)]);
+            assert_eq!(footprint.distribute(700), [(0, 0, 700)]);
+        }
+    }
+
+    #[test]
+    fn test_distribute_conserves_amount() {
+        let shares = Footprint::Disk { radius: 2 }.distribute(1000);
+        assert_eq!(shares.len(), 13);
+        assert_eq!(shares.iter().map(|&(_, _, a)| a).sum::<u32>(), 1000);
+
+        let huge = Footprint::Gaussian {
+            sigma: 1.5,
+            radius: 4,
+        }
+        .distribute(u32::MAX);
+        assert_eq!(
+            huge.iter().map(|&(_, _, a)| a as u64).sum::<u64>(),
+            u32::MAX as u64
+        );
+    }
+
+    #[test]
+    fn test_small_amount_spreads() {
+        // Fewer units than cells: one each, center first, never a needle
+        let shares = Footprint::Disk { radius: 3 }.distribute(10);
+        assert_eq!(shares.len(), 10);
+        assert_eq!(shares[0], (0, 0, 1));
+        assert!(shares.iter().all(|&(_, _, a)| a == 1));
+    }
+
+    #[test]
+    fn test_clamped_radius() {
+        let far = Footprint::Disk { radius: 1_000_000 }.clamped(8);
+        assert_eq!(far, Footprint::Disk { radius: 8 });
+        assert_eq!(Footprint::Point.clamped(0), Footprint::Point);
+    }
+}
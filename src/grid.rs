@@ -3,7 +3,11 @@
 //! Trace is NOT a data structure, it's a scalar field.
 //! No origin tracking (Source Amnesia), just density values.
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+use crate::error::TesError;
+use crate::field::TraceField;
+use crate::topology::{Boundary, Lattice, Topology};
 
 /// A 2D grid of trace density values.
 ///
@@ -23,14 +27,21 @@ pub struct DensityGrid {
     cells: Vec<AtomicU32>,
     /// Decay rate per tick (fixed-point, 1000 = 1.0)
     decay_rate: u32,
-    /// Solid threshold (above this = Solid regime)
-    solid_threshold: u32,
-    /// Liquid threshold (above this = Liquid regime)
-    liquid_threshold: u32,
+    /// How `decay_rate` is applied
+    decay_model: DecayModel,
+    /// Lattice shape (diffusion neighbors)
+    topology: Topology,
+    /// Edge behavior on a bounded plane
+    boundary: Boundary,
+    /// Regime boundaries (with hysteresis)
+    thresholds: RegimeThresholds,
+    /// Last settled regime per cell
+    regimes: Vec<AtomicU8>,
 }
 
 /// Phase regime at a given position
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Regime {
     /// High density - low permeability, fast saturation
     Solid,
@@ -40,6 +51,151 @@ pub enum Regime {
     Gas,
 }
 
+impl Regime {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            0 => Regime::Solid,
+            1 => Regime::Liquid,
+            _ => Regime::Gas,
+        }
+    }
+
+    /// Decode a stored regime byte; None if it names no regime.
+    pub(crate) fn decode(v: u8) -> Option<Self> {
+        (v <= Regime::Gas as u8).then(|| Self::from_u8(v))
+    }
+}
+
+/// How decay removes density each tick (δ).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DecayModel {
+    /// Subtract `decay_rate` density units per tick
+    #[default]
+    Linear,
+    /// Remove `decay_rate / 1000` of the density per tick (rounded up, so
+    /// trace still reaches zero)
+    Exponential,
+}
+
+impl DecayModel {
+    /// Density after one tick of decay.
+    #[inline]
+    pub fn apply(self, density: u32, rate: u32) -> u32 {
+        match self {
+            DecayModel::Linear => density.saturating_sub(rate),
+            DecayModel::Exponential => {
+                let removed = (density as u64 * rate as u64).div_ceil(1000);
+                density - removed.min(density as u64) as u32
+            }
+        }
+    }
+}
+
+/// Enter/exit boundaries for each regime.
+///
+/// A cell enters a regime when density rises above `*_enter` and leaves it
+/// only when density falls to `*_exit` or below. With `exit < enter`, a cell
+/// hovering at a boundary keeps its regime instead of flipping every tick.
+///
+/// Grids settle a cell's regime whenever its density changes (contribution,
+/// decay, diffusion), never on read, so the regime is a function of the
+/// density history alone and every observer sees the same value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegimeThresholds {
+    /// Density above which a cell becomes Solid
+    pub solid_enter: u32,
+    /// Density at or below which a Solid cell stops being Solid
+    pub solid_exit: u32,
+    /// Density above which a Gas cell becomes Liquid
+    pub liquid_enter: u32,
+    /// Density at or below which a cell falls back to Gas
+    pub liquid_exit: u32,
+}
+
+impl RegimeThresholds {
+    /// Single boundary per transition (no hysteresis).
+    pub fn new(solid_threshold: u32, liquid_threshold: u32) -> Self {
+        Self::with_margin(solid_threshold, liquid_threshold, 0)
+    }
+
+    /// Exit boundaries `margin` below the enter boundaries.
+    pub fn with_margin(solid_threshold: u32, liquid_threshold: u32, margin: u32) -> Self {
+        Self {
+            solid_enter: solid_threshold,
+            solid_exit: solid_threshold.saturating_sub(margin),
+            liquid_enter: liquid_threshold,
+            liquid_exit: liquid_threshold.saturating_sub(margin),
+        }
+    }
+
+    /// Check that the boundaries are ordered
+    /// `liquid_exit <= liquid_enter <= solid_exit <= solid_enter`: exits do
+    /// not lie above entries and the Solid band does not reach into Liquid.
+    pub fn validate(&self) -> Result<(), TesError> {
+        if self.liquid_exit > self.liquid_enter
+            || self.liquid_enter > self.solid_exit
+            || self.solid_exit > self.solid_enter
+        {
+            return Err(TesError::InvertedThresholds(*self));
+        }
+        Ok(())
+    }
+
+    /// Next regime of a cell currently in `current` at `density`.
+    ///
+    /// Depends only on the current regime and density, so a single change
+    /// crossing several boundaries settles in one step.
+    pub fn classify(&self, current: Regime, density: u32) -> Regime {
+        let solid = match current {
+            Regime::Solid => density > self.solid_exit,
+            _ => density > self.solid_enter,
+        };
+        let liquid = match current {
+            Regime::Gas => density > self.liquid_enter,
+            _ => density > self.liquid_exit,
+        };
+        if solid {
+            Regime::Solid
+        } else if liquid {
+            Regime::Liquid
+        } else {
+            Regime::Gas
+        }
+    }
+
+    /// Settle a stored regime against the current `density` of its cell.
+    ///
+    /// Density is reloaded on every retry, so the last of several racing
+    /// updaters settles against the latest density.
+    #[inline]
+    pub(crate) fn settle(&self, state: &AtomicU8, density: &AtomicU32) {
+        let _ = state.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |s| {
+            let next = self.classify(Regime::from_u8(s), density.load(Ordering::Relaxed));
+            Some(next as u8)
+        });
+    }
+
+    /// Settle every cell after a global update (decay, diffusion).
+    pub(crate) fn settle_all<'a>(
+        &self,
+        states: &[AtomicU8],
+        densities: impl IntoIterator<Item = &'a AtomicU32>,
+    ) {
+        for (state, density) in states.iter().zip(densities) {
+            self.settle(state, density);
+        }
+    }
+}
+
+/// Read a settled regime.
+#[inline]
+pub(crate) fn load_regime(state: &AtomicU8) -> Regime {
+    Regime::from_u8(state.load(Ordering::Relaxed))
+}
+
 impl DensityGrid {
     /// Create a new density grid.
     ///
@@ -56,18 +212,111 @@ impl DensityGrid {
         decay_rate: u32,
         solid_threshold: u32,
         liquid_threshold: u32,
+    ) -> Self {
+        let thresholds = RegimeThresholds::new(solid_threshold, liquid_threshold);
+        Self::with_regimes(width, height, decay_rate, thresholds)
+    }
+
+    /// Create a density grid, rejecting parameters [`DensityGrid::new`]
+    /// accepts silently: zero or oversized dimensions, inverted thresholds
+    /// and a decay rate above the Solid boundary.
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        solid_threshold: u32,
+        liquid_threshold: u32,
+    ) -> Result<Self, TesError> {
+        let thresholds = RegimeThresholds::new(solid_threshold, liquid_threshold);
+        Self::try_with_regimes(width, height, decay_rate, thresholds)
+    }
+
+    /// Fallible [`DensityGrid::with_regimes`].
+    pub fn try_with_regimes(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        check_grid(width, height, DecayModel::Linear, decay_rate, thresholds)?;
+        Ok(Self::with_regimes(width, height, decay_rate, thresholds))
+    }
+
+    /// Create a density grid with hysteretic regime boundaries.
+    pub fn with_regimes(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
     ) -> Self {
         let cells = (0..width * height)
             .map(|_| AtomicU32::new(0))
             .collect();
+        let regimes = (0..width * height)
+            .map(|_| AtomicU8::new(Regime::Gas as u8))
+            .collect();
 
         Self {
             width,
             height,
             cells,
             decay_rate,
-            solid_threshold,
-            liquid_threshold,
+            decay_model: DecayModel::default(),
+            topology: Topology::default(),
+            boundary: Boundary::default(),
+            thresholds,
+            regimes,
+        }
+    }
+
+    /// Change the regime boundaries; every cell settles against them.
+    pub fn set_regime_thresholds(&mut self, thresholds: RegimeThresholds) {
+        self.thresholds = thresholds;
+        thresholds.settle_all(&self.regimes, &self.cells);
+    }
+
+    /// Get the regime boundaries.
+    pub fn regime_thresholds(&self) -> RegimeThresholds {
+        self.thresholds
+    }
+
+    /// Get decay per tick (fixed-point, 1000 = 1.0).
+    pub fn decay_rate(&self) -> u32 {
+        self.decay_rate
+    }
+
+    /// Set how decay is applied (default linear).
+    pub fn set_decay_model(&mut self, model: DecayModel) {
+        self.decay_model = model;
+    }
+
+    /// Get how decay is applied.
+    pub fn decay_model(&self) -> DecayModel {
+        self.decay_model
+    }
+
+    /// Set lattice topology and edge behavior (default closed plane).
+    pub fn set_topology(&mut self, topology: Topology, boundary: Boundary) {
+        self.topology = topology;
+        self.boundary = boundary;
+    }
+
+    /// Get lattice topology.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Get edge behavior.
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    fn lattice(&self) -> Lattice {
+        Lattice {
+            width: self.width,
+            height: self.height,
+            topology: self.topology,
+            boundary: self.boundary,
         }
     }
 
@@ -77,11 +326,36 @@ impl DensityGrid {
     /// No origin information is stored (Source Amnesia).
     #[inline]
     pub fn contribute(&self, x: usize, y: usize, amount: u32) {
-        if let Some(cell) = self.get_cell(x, y) {
-            cell.fetch_add(amount, Ordering::Relaxed);
+        if let Some(i) = self.index(x, y) {
+            self.cells[i].fetch_add(amount, Ordering::Relaxed);
+            self.thresholds.settle(&self.regimes[i], &self.cells[i]);
         }
     }
 
+    /// Contribute trace only if position is habitable.
+    ///
+    /// Atomic check-and-contribute (single CAS): concurrent callers can
+    /// never push a cell past `threshold` by more than one contribution (A1).
+    /// Cells held Solid are rejected (see [`DensityGrid::is_habitable`]).
+    #[inline]
+    pub fn try_contribute(&self, x: usize, y: usize, amount: u32, threshold: u32) -> bool {
+        let Some(i) = self.index(x, y) else {
+            return false;
+        };
+        if load_regime(&self.regimes[i]) == Regime::Solid {
+            return false;
+        }
+        let admitted = self.cells[i]
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |d| {
+                (d < threshold).then(|| d + amount)
+            })
+            .is_ok();
+        if admitted {
+            self.thresholds.settle(&self.regimes[i], &self.cells[i]);
+        }
+        admitted
+    }
+
     /// Get current density at position.
     #[inline]
     pub fn density(&self, x: usize, y: usize) -> u32 {
@@ -90,37 +364,52 @@ impl DensityGrid {
             .unwrap_or(0)
     }
 
-    /// Get regime at position.
+    /// Restore a cell's density and the regime it was held in (snapshot
+    /// restore); the regime then settles against the density as usual.
+    pub(crate) fn restore(&self, x: usize, y: usize, density: u32, regime: Regime) {
+        if let Some(i) = self.index(x, y) {
+            self.regimes[i].store(regime as u8, Ordering::Relaxed);
+            self.cells[i].store(density, Ordering::Relaxed);
+            self.thresholds.settle(&self.regimes[i], &self.cells[i]);
+        }
+    }
+
+    /// Get regime at position, as settled by the last density change
+    /// (see [`RegimeThresholds`]).
     #[inline]
     pub fn regime(&self, x: usize, y: usize) -> Regime {
-        let d = self.density(x, y);
-        if d > self.solid_threshold {
-            Regime::Solid
-        } else if d > self.liquid_threshold {
-            Regime::Liquid
-        } else {
-            Regime::Gas
-        }
+        self.index(x, y)
+            .map_or(Regime::Gas, |i| load_regime(&self.regimes[i]))
     }
 
     /// Check if position is habitable (not saturated).
     ///
-    /// This is the core habitability check from A1.
+    /// This is the core habitability check from A1. A Solid cell stays
+    /// uninhabitable until it falls to the Solid exit boundary.
     #[inline]
     pub fn is_habitable(&self, x: usize, y: usize, threshold: u32) -> bool {
-        self.density(x, y) < threshold
+        self.density(x, y) < threshold && self.regime(x, y) != Regime::Solid
     }
 
     /// Apply global decay to entire grid.
     ///
     /// This is the δ projection function.
     /// Called once per tick.
+    ///
+    /// Read-modify-write per cell, so concurrent contributions are kept.
     pub fn apply_decay(&self) {
-        for cell in &self.cells {
-            let current = cell.load(Ordering::Relaxed);
-            let new_value = current.saturating_sub(self.decay_rate);
-            cell.store(new_value, Ordering::Relaxed);
-        }
+        decay_cells(&self.cells, self.decay_model, self.decay_rate);
+        self.thresholds.settle_all(&self.regimes, &self.cells);
+    }
+
+    /// Diffusion: Density leaks to neighbors (ENERGY CONSERVING).
+    ///
+    /// Same kernel as `IsotopeGrid::diffuse`: 12.5% per 4-connected
+    /// neighbor. On a closed plane edge cells do not leak; on an open plane
+    /// trace leaking off the edge is lost (see [`Boundary`]).
+    pub fn diffuse(&self) {
+        diffuse_cells(&self.cells, self.lattice());
+        self.thresholds.settle_all(&self.regimes, &self.cells);
     }
 
     /// Get dimensions
@@ -128,16 +417,159 @@ impl DensityGrid {
         (self.width, self.height)
     }
 
+    /// Cell densities in row-major order (zero-copy views).
+    #[cfg(feature = "python")]
+    pub(crate) fn cells(&self) -> &[AtomicU32] {
+        &self.cells
+    }
+
     #[inline]
     fn get_cell(&self, x: usize, y: usize) -> Option<&AtomicU32> {
-        if x < self.width && y < self.height {
-            Some(&self.cells[y * self.width + x])
-        } else {
-            None
+        self.index(x, y).map(|i| &self.cells[i])
+    }
+
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+}
+
+impl TraceField for DensityGrid {
+    fn dimensions(&self) -> (usize, usize) {
+        self.dimensions()
+    }
+
+    fn contribute(&self, x: usize, y: usize, amount: u32) {
+        self.contribute(x, y, amount);
+    }
+
+    fn try_contribute(&self, x: usize, y: usize, amount: u32, threshold: u32) -> bool {
+        self.try_contribute(x, y, amount, threshold)
+    }
+
+    fn density(&self, x: usize, y: usize) -> u32 {
+        self.density(x, y)
+    }
+
+    fn regime(&self, x: usize, y: usize) -> Regime {
+        self.regime(x, y)
+    }
+
+    fn regime_thresholds(&self) -> RegimeThresholds {
+        self.regime_thresholds()
+    }
+
+    fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    fn apply_decay(&self) {
+        self.apply_decay();
+    }
+
+    fn diffuse(&self) {
+        self.diffuse();
+    }
+}
+
+/// Decay every cell of a monochrome layout.
+pub(crate) fn decay_cells(cells: &[AtomicU32], model: DecayModel, rate: u32) {
+    for cell in cells {
+        let _ = cell.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |d| {
+            Some(model.apply(d, rate))
+        });
+    }
+}
+
+/// Diffuse a monochrome layout (see [`DensityGrid::diffuse`]).
+pub(crate) fn diffuse_cells(cells: &[AtomicU32], lattice: Lattice) {
+    for (x, y) in lattice.sources() {
+        let center = &cells[y * lattice.width + x];
+        let d = center.load(Ordering::Relaxed);
+
+        // Skip low-density cells (optimization)
+        if d < 40 {
+            continue;
+        }
+
+        let leak = d >> 3;
+        center.fetch_sub(leak * 4, Ordering::Relaxed);
+        for n_idx in lattice.neighbors(x, y).into_iter().flatten() {
+            cells[n_idx].fetch_add(leak, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Widest per-cell storage of any layout (isotope channels + regime).
+const MAX_CELL_BYTES: usize = 4 * 4 + 1;
+
+/// Number of cells of a `width × height` grid, if it can be allocated.
+///
+/// Each dimension must fit a snapshot header (u32) and the cells must fit
+/// in memory in the widest layout.
+pub(crate) fn cell_count(width: usize, height: usize) -> Option<usize> {
+    let fits = |v: usize| u32::try_from(v).is_ok();
+    if !fits(width) || !fits(height) {
+        return None;
+    }
+    let cells = width.checked_mul(height)?;
+    cells
+        .checked_mul(MAX_CELL_BYTES)
+        .filter(|&bytes| bytes <= isize::MAX as usize)?;
+    Some(cells)
+}
+
+/// Validate grid parameters.
+///
+/// Rejects zero or oversized dimensions, inverted thresholds, a linear
+/// decay rate above the Solid boundary (trace could never accumulate) and
+/// an exponential one above 1000 (more than the whole density).
+///
+/// The one rule set behind every constructor, restore path and
+/// [`SpaceConfig`](crate::SpaceConfig).
+pub(crate) fn check_grid(
+    width: usize,
+    height: usize,
+    decay_model: DecayModel,
+    decay_rate: u32,
+    thresholds: RegimeThresholds,
+) -> Result<(), TesError> {
+    if width == 0 || height == 0 {
+        return Err(TesError::ZeroDimension);
+    }
+    if cell_count(width, height).is_none() {
+        return Err(TesError::TooLarge { width, height });
+    }
+    thresholds.validate()?;
+    match decay_model {
+        DecayModel::Linear if decay_rate > thresholds.solid_enter => {
+            Err(TesError::DecayExceedsThreshold {
+                decay_rate,
+                threshold: thresholds.solid_enter,
+            })
         }
+        DecayModel::Exponential if decay_rate > 1000 => Err(TesError::DecayAboveOne(decay_rate)),
+        _ => Ok(()),
     }
 }
 
+/// Check a habitability threshold against a field's regimes.
+///
+/// It must be positive and must not exceed the Solid entry boundary, or
+/// cells would be admitted into the Solid regime.
+pub(crate) fn check_threshold(
+    threshold: u32,
+    thresholds: RegimeThresholds,
+) -> Result<(), TesError> {
+    if threshold == 0 || threshold > thresholds.solid_enter {
+        return Err(TesError::ThresholdOutOfRange {
+            threshold,
+            solid: thresholds.solid_enter,
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,6 +603,87 @@ mod tests {
         assert_eq!(grid.regime(0, 0), Regime::Solid);
     }
 
+    #[test]
+    fn test_regime_hysteresis_holds_at_boundary() {
+        let thresholds = RegimeThresholds::with_margin(1000, 500, 50);
+        let grid = DensityGrid::with_regimes(10, 10, 10, thresholds);
+
+        grid.contribute(0, 0, 1001);
+        assert_eq!(grid.regime(0, 0), Regime::Solid);
+
+        // Hovering around the enter boundary: decay, refill, decay...
+        for _ in 0..10 {
+            grid.apply_decay();
+            assert_eq!(grid.regime(0, 0), Regime::Solid);
+            grid.contribute(0, 0, 10);
+            assert_eq!(grid.regime(0, 0), Regime::Solid);
+        }
+
+        // Leaves only at the exit boundary
+        grid.apply_decay();
+        grid.apply_decay();
+        grid.apply_decay();
+        grid.apply_decay();
+        assert_eq!(grid.density(0, 0), 961);
+        assert_eq!(grid.regime(0, 0), Regime::Solid);
+        grid.apply_decay();
+        grid.apply_decay();
+        assert_eq!(grid.regime(0, 0), Regime::Liquid);
+
+        // Re-entering needs the enter boundary again
+        grid.contribute(0, 0, 50);
+        assert_eq!(grid.regime(0, 0), Regime::Liquid);
+    }
+
+    #[test]
+    fn test_regime_without_margin_flips() {
+        let grid = DensityGrid::new(10, 10, 10, 1000, 500);
+        grid.contribute(0, 0, 1001);
+        assert_eq!(grid.regime(0, 0), Regime::Solid);
+        grid.apply_decay();
+        assert_eq!(grid.regime(0, 0), Regime::Liquid);
+    }
+
+    #[test]
+    fn test_regime_settles_across_levels() {
+        let thresholds = RegimeThresholds::with_margin(1000, 500, 100);
+        let grid = DensityGrid::with_regimes(10, 10, 0, thresholds);
+
+        // One contribution passes through Liquid
+        grid.contribute(2, 2, 2000);
+        assert_eq!(grid.regime(2, 2), Regime::Solid);
+
+        let grid = DensityGrid::with_regimes(10, 10, 1000, thresholds);
+        grid.contribute(2, 2, 1500);
+        assert_eq!(grid.regime(2, 2), Regime::Solid);
+        grid.apply_decay();
+        assert_eq!(grid.density(2, 2), 500);
+        // Below Solid exit, above Liquid exit
+        assert_eq!(grid.regime(2, 2), Regime::Liquid);
+        grid.apply_decay();
+        assert_eq!(grid.regime(2, 2), Regime::Gas);
+    }
+
+    #[test]
+    fn test_regime_independent_of_reads() {
+        let thresholds = RegimeThresholds::with_margin(1000, 500, 50);
+        let watched = DensityGrid::with_regimes(4, 4, 10, thresholds);
+        let unwatched = DensityGrid::with_regimes(4, 4, 10, thresholds);
+
+        for grid in [&watched, &unwatched] {
+            grid.contribute(1, 1, 1001);
+        }
+        assert_eq!(watched.regime(1, 1), Regime::Solid);
+        for grid in [&watched, &unwatched] {
+            grid.apply_decay();
+        }
+
+        // First read of `unwatched` is below the Solid entry: still Solid
+        assert_eq!(unwatched.density(1, 1), 991);
+        assert_eq!(unwatched.regime(1, 1), Regime::Solid);
+        assert_eq!(watched.regime(1, 1), unwatched.regime(1, 1));
+    }
+
     #[test]
     fn test_source_amnesia() {
         let grid = DensityGrid::new(10, 10, 0, 1000, 500);
@@ -185,4 +698,71 @@ mod tests {
         assert_eq!(grid.density(3, 3), 100);
         // Source information is LOST by design
     }
+
+    #[test]
+    fn test_try_contribute_respects_threshold() {
+        let grid = DensityGrid::new(10, 10, 0, 1000, 500);
+
+        // Admitted while below threshold, even if it overshoots
+        assert!(grid.try_contribute(1, 1, 60, 100));
+        assert!(grid.try_contribute(1, 1, 60, 100));
+        assert!(!grid.try_contribute(1, 1, 60, 100));
+        assert_eq!(grid.density(1, 1), 120);
+    }
+
+    #[test]
+    fn test_diffusion_conserves_mass() {
+        let grid = DensityGrid::new(10, 10, 0, 1000, 500);
+        grid.contribute(5, 5, 800);
+
+        grid.diffuse();
+
+        // Center leaked 12.5% to each neighbor (row above is already visited)
+        assert!(grid.density(5, 5) < 800);
+        assert_eq!(grid.density(5, 4), 100);
+
+        let total: u32 = (0..10)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .map(|(x, y)| grid.density(x, y))
+            .sum();
+        assert_eq!(total, 800);
+    }
+
+    #[test]
+    fn test_try_new_validates() {
+        assert!(DensityGrid::try_new(8, 8, 5, 1000, 500).is_ok());
+        assert!(matches!(
+            DensityGrid::try_new(0, 8, 5, 1000, 500),
+            Err(TesError::ZeroDimension)
+        ));
+        assert!(matches!(
+            DensityGrid::try_new(1 << 33, 4, 5, 1000, 500),
+            Err(TesError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            DensityGrid::try_new(8, 8, 5, 500, 1000),
+            Err(TesError::InvertedThresholds(_))
+        ));
+        assert!(matches!(
+            DensityGrid::try_new(8, 8, 1001, 1000, 500),
+            Err(TesError::DecayExceedsThreshold { .. })
+        ));
+
+        let mut t = RegimeThresholds::new(1000, 500);
+        t.liquid_exit = 600;
+        assert!(t.validate().is_err());
+        // Solid band reaching below the Liquid entry
+        assert!(RegimeThresholds::with_margin(1000, 500, 600)
+            .validate()
+            .is_err());
+        // Liquid may coincide with Solid
+        assert!(RegimeThresholds::new(1000, 1000).validate().is_ok());
+
+        let t = RegimeThresholds::new(100, 50);
+        assert!(check_grid(8, 8, DecayModel::Exponential, 500, t).is_ok());
+        assert!(matches!(
+            check_grid(8, 8, DecayModel::Exponential, 1001, t),
+            Err(TesError::DecayAboveOne(1001))
+        ));
+    }
 }
@@ -8,7 +8,12 @@
 //! - Bitmask: "A and B are here" (loses proportion)
 //! - RGB Vector: "90% A, 10% B are here" (preserves proportion)
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+use crate::error::TesError;
+use crate::field::TraceField;
+use crate::grid::{check_grid, load_regime, DecayModel, Regime, RegimeThresholds};
+use crate::topology::{Boundary, Lattice, Topology};
 
 /// A single trace pixel with RGB color components.
 ///
@@ -16,7 +21,11 @@ use std::sync::atomic::{AtomicU32, Ordering};
 /// The color is determined by hashing the service name.
 ///
 /// # Memory
-/// 12 bytes per cell (3 × 4 bytes) - same as bitmask approach.
+/// 16 bytes per cell: 12 bytes of channels (3 × 4 bytes, same as bitmask
+/// approach) plus a 4-byte total used for atomic habitability checks.
+/// Laid out as `r, g, b, total` (`repr(C)`) so cells can be viewed as raw
+/// words without copying.
+#[repr(C)]
 pub struct TracePixel {
     /// Red component (accumulated)
     r: AtomicU32,
@@ -24,6 +33,8 @@ pub struct TracePixel {
     g: AtomicU32,
     /// Blue component (accumulated)
     b: AtomicU32,
+    /// Total density (r + g + b), kept in step with the channels
+    total: AtomicU32,
 }
 
 impl TracePixel {
@@ -32,25 +43,55 @@ impl TracePixel {
             r: AtomicU32::new(0),
             g: AtomicU32::new(0),
             b: AtomicU32::new(0),
+            total: AtomicU32::new(0),
         }
     }
 
-    /// Add contribution with color signature.
+    /// Scale contribution by color components.
     ///
     /// Color is in fixed-point (1000 = 1.0).
-    fn contribute(&self, amount: u32, color: (u32, u32, u32)) {
+    fn split(amount: u32, color: (u32, u32, u32)) -> (u32, u32, u32) {
         let (cr, cg, cb) = color;
-        // Scale contribution by color components
-        self.r.fetch_add((amount * cr) / 1000, Ordering::Relaxed);
-        self.g.fetch_add((amount * cg) / 1000, Ordering::Relaxed);
-        self.b.fetch_add((amount * cb) / 1000, Ordering::Relaxed);
+        (
+            (amount * cr) / 1000,
+            (amount * cg) / 1000,
+            (amount * cb) / 1000,
+        )
+    }
+
+    /// Add contribution with color signature.
+    fn contribute(&self, amount: u32, color: (u32, u32, u32)) {
+        let (dr, dg, db) = Self::split(amount, color);
+        self.total.fetch_add(dr + dg + db, Ordering::Relaxed);
+        self.add_channels(dr, dg, db);
+    }
+
+    /// Add contribution only if total density is below `threshold`.
+    ///
+    /// The check and the reservation are a single CAS on the total.
+    fn try_contribute(&self, amount: u32, color: (u32, u32, u32), threshold: u32) -> bool {
+        let (dr, dg, db) = Self::split(amount, color);
+        let admitted = self
+            .total
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |t| {
+                (t < threshold).then(|| t + dr + dg + db)
+            })
+            .is_ok();
+        if admitted {
+            self.add_channels(dr, dg, db);
+        }
+        admitted
+    }
+
+    fn add_channels(&self, dr: u32, dg: u32, db: u32) {
+        self.r.fetch_add(dr, Ordering::Relaxed);
+        self.g.fetch_add(dg, Ordering::Relaxed);
+        self.b.fetch_add(db, Ordering::Relaxed);
     }
 
     /// Get total density (r + g + b).
     fn density(&self) -> u32 {
-        self.r.load(Ordering::Relaxed)
-            + self.g.load(Ordering::Relaxed)
-            + self.b.load(Ordering::Relaxed)
+        self.total.load(Ordering::Relaxed)
     }
 
     /// Get RGB values.
@@ -65,11 +106,11 @@ impl TracePixel {
     /// Get normalized color (for visualization).
     /// Returns (r, g, b) where each is 0.0-1.0.
     fn normalized_color(&self) -> (f32, f32, f32) {
-        let total = self.density();
+        let (r, g, b) = self.rgb();
+        let total = r + g + b;
         if total == 0 {
             return (0.0, 0.0, 0.0);
         }
-        let (r, g, b) = self.rgb();
         (
             r as f32 / total as f32,
             g as f32 / total as f32,
@@ -78,13 +119,22 @@ impl TracePixel {
     }
 
     /// Apply decay to all channels.
-    fn decay(&self, rate: u32) {
-        let r = self.r.load(Ordering::Relaxed);
-        let g = self.g.load(Ordering::Relaxed);
-        let b = self.b.load(Ordering::Relaxed);
-        self.r.store(r.saturating_sub(rate), Ordering::Relaxed);
-        self.g.store(g.saturating_sub(rate), Ordering::Relaxed);
-        self.b.store(b.saturating_sub(rate), Ordering::Relaxed);
+    ///
+    /// Read-modify-write per channel, so concurrent contributions are kept.
+    fn decay(&self, model: DecayModel, rate: u32) {
+        let removed: u32 = [&self.r, &self.g, &self.b]
+            .into_iter()
+            .map(|channel| {
+                let mut removed = 0;
+                let _ = channel.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                    let next = model.apply(v, rate);
+                    removed = v - next;
+                    Some(next)
+                });
+                removed
+            })
+            .sum();
+        self.total.fetch_sub(removed, Ordering::Relaxed);
     }
 }
 
@@ -94,13 +144,17 @@ pub struct IsotopeGrid {
     height: usize,
     cells: Vec<TracePixel>,
     decay_rate: u32,
-    solid_threshold: u32,
-    liquid_threshold: u32,
+    decay_model: DecayModel,
+    topology: Topology,
+    boundary: Boundary,
+    thresholds: RegimeThresholds,
+    regimes: Vec<AtomicU8>,
 }
 
 /// Service color signature.
 /// Generated by hashing service name to RGB.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServiceColor {
     pub r: u32, // 0-1000 (fixed point)
     pub g: u32,
@@ -122,6 +176,17 @@ impl ServiceColor {
         }
     }
 
+    /// Create an evenly split color.
+    ///
+    /// Used for uncolored trace: total density equals the contribution.
+    pub fn neutral() -> Self {
+        Self {
+            r: 334,
+            g: 333,
+            b: 333,
+        }
+    }
+
     /// Create a pure red color (for testing).
     pub fn red() -> Self {
         Self {
@@ -171,15 +236,107 @@ impl IsotopeGrid {
         decay_rate: u32,
         solid_threshold: u32,
         liquid_threshold: u32,
+    ) -> Self {
+        let thresholds = RegimeThresholds::new(solid_threshold, liquid_threshold);
+        Self::with_regimes(width, height, decay_rate, thresholds)
+    }
+
+    /// Create an isotope grid, rejecting invalid parameters (see
+    /// [`DensityGrid::try_new`](crate::DensityGrid::try_new)).
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        solid_threshold: u32,
+        liquid_threshold: u32,
+    ) -> Result<Self, TesError> {
+        let thresholds = RegimeThresholds::new(solid_threshold, liquid_threshold);
+        Self::try_with_regimes(width, height, decay_rate, thresholds)
+    }
+
+    /// Fallible [`IsotopeGrid::with_regimes`].
+    pub fn try_with_regimes(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        check_grid(width, height, DecayModel::Linear, decay_rate, thresholds)?;
+        Ok(Self::with_regimes(width, height, decay_rate, thresholds))
+    }
+
+    /// Create an isotope grid with hysteretic regime boundaries.
+    pub fn with_regimes(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
     ) -> Self {
         let cells = (0..width * height).map(|_| TracePixel::new()).collect();
+        let regimes = (0..width * height)
+            .map(|_| AtomicU8::new(Regime::Gas as u8))
+            .collect();
         Self {
             width,
             height,
             cells,
             decay_rate,
-            solid_threshold,
-            liquid_threshold,
+            decay_model: DecayModel::default(),
+            topology: Topology::default(),
+            boundary: Boundary::default(),
+            thresholds,
+            regimes,
+        }
+    }
+
+    /// Change the regime boundaries; every cell settles against them.
+    pub fn set_regime_thresholds(&mut self, thresholds: RegimeThresholds) {
+        self.thresholds = thresholds;
+        self.settle_all();
+    }
+
+    /// Get the regime boundaries.
+    pub fn regime_thresholds(&self) -> RegimeThresholds {
+        self.thresholds
+    }
+
+    /// Get decay per tick (fixed-point, 1000 = 1.0).
+    pub fn decay_rate(&self) -> u32 {
+        self.decay_rate
+    }
+
+    /// Set how decay is applied to each channel (default linear).
+    pub fn set_decay_model(&mut self, model: DecayModel) {
+        self.decay_model = model;
+    }
+
+    /// Get how decay is applied.
+    pub fn decay_model(&self) -> DecayModel {
+        self.decay_model
+    }
+
+    /// Set lattice topology and edge behavior (default closed plane).
+    pub fn set_topology(&mut self, topology: Topology, boundary: Boundary) {
+        self.topology = topology;
+        self.boundary = boundary;
+    }
+
+    /// Get lattice topology.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Get edge behavior.
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    fn lattice(&self) -> Lattice {
+        Lattice {
+            width: self.width,
+            height: self.height,
+            topology: self.topology,
+            boundary: self.boundary,
         }
     }
 
@@ -189,11 +346,38 @@ impl IsotopeGrid {
     /// Preserves proportion information (unlike bitmask).
     #[inline]
     pub fn contribute(&self, x: usize, y: usize, amount: u32, color: ServiceColor) {
-        if let Some(cell) = self.get_cell(x, y) {
-            cell.contribute(amount, color.as_tuple());
+        if let Some(i) = self.index(x, y) {
+            self.cells[i].contribute(amount, color.as_tuple());
+            self.settle(i);
         }
     }
 
+    /// Contribute trace only if position is habitable.
+    ///
+    /// Atomic check-and-contribute: concurrent callers can never push a
+    /// cell past `threshold` by more than one contribution (A1).
+    #[inline]
+    pub fn try_contribute(
+        &self,
+        x: usize,
+        y: usize,
+        amount: u32,
+        color: ServiceColor,
+        threshold: u32,
+    ) -> bool {
+        let Some(i) = self.index(x, y) else {
+            return false;
+        };
+        if load_regime(&self.regimes[i]) == Regime::Solid {
+            return false;
+        }
+        let admitted = self.cells[i].try_contribute(amount, color.as_tuple(), threshold);
+        if admitted {
+            self.settle(i);
+        }
+        admitted
+    }
+
     /// Get total density at position.
     #[inline]
     pub fn density(&self, x: usize, y: usize) -> u32 {
@@ -231,17 +415,39 @@ impl IsotopeGrid {
         }
     }
 
-    /// Check if position is habitable.
+    /// Restore raw channel values and the regime the cell was held in
+    /// (snapshot restore); the regime then settles as usual.
+    pub(crate) fn restore(&self, x: usize, y: usize, (r, g, b): (u32, u32, u32), regime: Regime) {
+        if let Some(i) = self.index(x, y) {
+            let cell = &self.cells[i];
+            self.regimes[i].store(regime as u8, Ordering::Relaxed);
+            cell.total.fetch_add(r + g + b, Ordering::Relaxed);
+            cell.add_channels(r, g, b);
+            self.settle(i);
+        }
+    }
+
+    /// Get regime at position (from total density, with hysteresis), as
+    /// settled by the last density change.
+    #[inline]
+    pub fn regime(&self, x: usize, y: usize) -> Regime {
+        self.index(x, y)
+            .map_or(Regime::Gas, |i| load_regime(&self.regimes[i]))
+    }
+
+    /// Check if position is habitable (held while Solid, like
+    /// [`DensityGrid::is_habitable`](crate::DensityGrid::is_habitable)).
     #[inline]
     pub fn is_habitable(&self, x: usize, y: usize, threshold: u32) -> bool {
-        self.density(x, y) < threshold
+        self.density(x, y) < threshold && self.regime(x, y) != Regime::Solid
     }
 
     /// Apply global decay to all channels.
     pub fn apply_decay(&self) {
         for cell in &self.cells {
-            cell.decay(self.decay_rate);
+            cell.decay(self.decay_model, self.decay_rate);
         }
+        self.settle_all();
     }
 
     /// Get dimensions.
@@ -249,81 +455,159 @@ impl IsotopeGrid {
         (self.width, self.height)
     }
 
+    /// Cells as raw words, four per cell in [`TracePixel`] order
+    /// (`r, g, b, total`), row-major (zero-copy views).
+    #[cfg(feature = "python")]
+    pub(crate) fn words(&self) -> &[AtomicU32] {
+        // SAFETY: TracePixel is repr(C) with four AtomicU32 fields and no
+        // padding; the slice covers exactly the cells' memory.
+        unsafe { std::slice::from_raw_parts(self.cells.as_ptr().cast(), self.cells.len() * 4) }
+    }
+
     #[inline]
     fn get_cell(&self, x: usize, y: usize) -> Option<&TracePixel> {
-        if x < self.width && y < self.height {
-            Some(&self.cells[y * self.width + x])
-        } else {
-            None
-        }
+        self.index(x, y).map(|i| &self.cells[i])
+    }
+
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+
+    /// Settle the regime of cell `i` against its total density.
+    #[inline]
+    fn settle(&self, i: usize) {
+        self.thresholds
+            .settle(&self.regimes[i], &self.cells[i].total);
+    }
+
+    fn settle_all(&self) {
+        let totals = self.cells.iter().map(|c| &c.total);
+        self.thresholds.settle_all(&self.regimes, totals);
     }
 
     /// Diffusion: Density leaks to neighbors (ENERGY CONSERVING).
     /// This creates the "liquid" phase - pixels connect instead of staying isolated.
     /// Subtracts from center what it adds to neighbors.
+    /// Edges follow the grid's [`Topology`] and [`Boundary`].
     pub fn diffuse(&self) {
-        let w = self.width;
-        let h = self.height;
+        let lattice = self.lattice();
 
         // Cross pattern diffusion (4-connectivity)
-        // Skip edges to avoid bounds checking
-        for y in 1..h - 1 {
-            for x in 1..w - 1 {
-                let center_idx = y * w + x;
-                let center = &self.cells[center_idx];
-
-                // Skip low-density cells (optimization)
-                if center.density() < 40 {
-                    continue;
-                }
+        for (x, y) in lattice.sources() {
+            let center_idx = y * self.width + x;
+            let center = &self.cells[center_idx];
+
+            // Skip low-density cells (optimization)
+            if center.density() < 40 {
+                continue;
+            }
 
-                // Get current RGB
-                let (r, g, b) = center.rgb();
-
-                // Lower threshold for fluid effect (was 30, now 4)
-                if r > 4 || g > 4 || b > 4 {
-                    // 12.5% leak per neighbor (50% total) - bitwise shift is faster
-                    let leak_r = r >> 3; // r / 8
-                    let leak_g = g >> 3;
-                    let leak_b = b >> 3;
-
-                    // Only proceed if there's something to leak
-                    if leak_r > 0 || leak_g > 0 || leak_b > 0 {
-                        // Total amount to remove from center (4 neighbors)
-                        let total_leak_r = leak_r * 4;
-                        let total_leak_g = leak_g * 4;
-                        let total_leak_b = leak_b * 4;
-
-                        // SUBTRACT from center first (energy conservation)
-                        center.r.fetch_sub(total_leak_r.min(r), Ordering::Relaxed);
-                        center.g.fetch_sub(total_leak_g.min(g), Ordering::Relaxed);
-                        center.b.fetch_sub(total_leak_b.min(b), Ordering::Relaxed);
-
-                        // Neighbor indices
-                        let neighbors = [
-                            (y - 1) * w + x, // Up
-                            (y + 1) * w + x, // Down
-                            y * w + (x - 1), // Left
-                            y * w + (x + 1), // Right
-                        ];
-
-                        // ADD to neighbors
-                        for n_idx in neighbors {
-                            let neighbor = &self.cells[n_idx];
-                            if leak_r > 0 {
-                                neighbor.r.fetch_add(leak_r, Ordering::Relaxed);
-                            }
-                            if leak_g > 0 {
-                                neighbor.g.fetch_add(leak_g, Ordering::Relaxed);
-                            }
-                            if leak_b > 0 {
-                                neighbor.b.fetch_add(leak_b, Ordering::Relaxed);
-                            }
+            // Get current RGB
+            let (r, g, b) = center.rgb();
+
+            // Lower threshold for fluid effect (was 30, now 4)
+            if r > 4 || g > 4 || b > 4 {
+                // 12.5% leak per neighbor (50% total) - bitwise shift is faster
+                let leak_r = r >> 3; // r / 8
+                let leak_g = g >> 3;
+                let leak_b = b >> 3;
+
+                // Only proceed if there's something to leak
+                if leak_r > 0 || leak_g > 0 || leak_b > 0 {
+                    // Total amount to remove from center (4 neighbors)
+                    let total_leak_r = leak_r * 4;
+                    let total_leak_g = leak_g * 4;
+                    let total_leak_b = leak_b * 4;
+
+                    // SUBTRACT from center first (energy conservation)
+                    center.r.fetch_sub(total_leak_r.min(r), Ordering::Relaxed);
+                    center.g.fetch_sub(total_leak_g.min(g), Ordering::Relaxed);
+                    center.b.fetch_sub(total_leak_b.min(b), Ordering::Relaxed);
+                    center.total.fetch_sub(
+                        total_leak_r.min(r) + total_leak_g.min(g) + total_leak_b.min(b),
+                        Ordering::Relaxed,
+                    );
+
+                    // ADD to neighbors (off an open plane: lost)
+                    for n_idx in lattice.neighbors(x, y).into_iter().flatten() {
+                        let neighbor = &self.cells[n_idx];
+                        if leak_r > 0 {
+                            neighbor.r.fetch_add(leak_r, Ordering::Relaxed);
+                        }
+                        if leak_g > 0 {
+                            neighbor.g.fetch_add(leak_g, Ordering::Relaxed);
                         }
+                        if leak_b > 0 {
+                            neighbor.b.fetch_add(leak_b, Ordering::Relaxed);
+                        }
+                        neighbor
+                            .total
+                            .fetch_add(leak_r + leak_g + leak_b, Ordering::Relaxed);
                     }
                 }
             }
         }
+
+        self.settle_all();
+    }
+}
+
+impl TraceField for IsotopeGrid {
+    fn dimensions(&self) -> (usize, usize) {
+        self.dimensions()
+    }
+
+    /// Uncolored trace is split evenly over all channels.
+    fn contribute(&self, x: usize, y: usize, amount: u32) {
+        self.contribute(x, y, amount, ServiceColor::neutral());
+    }
+
+    fn contribute_colored(&self, x: usize, y: usize, amount: u32, color: ServiceColor) {
+        self.contribute(x, y, amount, color);
+    }
+
+    fn try_contribute(&self, x: usize, y: usize, amount: u32, threshold: u32) -> bool {
+        self.try_contribute(x, y, amount, ServiceColor::neutral(), threshold)
+    }
+
+    fn try_contribute_colored(
+        &self,
+        x: usize,
+        y: usize,
+        amount: u32,
+        color: ServiceColor,
+        threshold: u32,
+    ) -> bool {
+        self.try_contribute(x, y, amount, color, threshold)
+    }
+
+    fn density(&self, x: usize, y: usize) -> u32 {
+        self.density(x, y)
+    }
+
+    fn regime(&self, x: usize, y: usize) -> Regime {
+        self.regime(x, y)
+    }
+
+    fn channels(&self, x: usize, y: usize) -> Option<(u32, u32, u32)> {
+        Some(self.rgb(x, y))
+    }
+
+    fn regime_thresholds(&self) -> RegimeThresholds {
+        self.regime_thresholds()
+    }
+
+    fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    fn apply_decay(&self) {
+        self.apply_decay();
+    }
+
+    fn diffuse(&self) {
+        self.diffuse();
     }
 }
 
@@ -411,4 +695,63 @@ mod tests {
         grid.contribute(1, 1, 200, ServiceColor::green());
         assert_eq!(grid.dominant_channel(1, 1), Some('G'));
     }
+
+    #[test]
+    fn test_try_contribute_respects_threshold() {
+        let grid = IsotopeGrid::new(10, 10, 0, 1000, 500);
+
+        assert!(grid.try_contribute(0, 0, 600, ServiceColor::red(), 1000));
+        assert!(grid.try_contribute(0, 0, 600, ServiceColor::blue(), 1000));
+        assert!(!grid.try_contribute(0, 0, 1, ServiceColor::green(), 1000));
+        assert_eq!(grid.rgb(0, 0), (600, 0, 600));
+
+        // Out of bounds is never habitable
+        assert!(!grid.try_contribute(10, 0, 1, ServiceColor::red(), 1000));
+    }
+
+    #[test]
+    fn test_regime_from_total_density() {
+        let grid = IsotopeGrid::new(10, 10, 0, 1000, 500);
+        assert_eq!(grid.regime(2, 2), Regime::Gas);
+
+        // Channels add up to Liquid, then Solid
+        grid.contribute(2, 2, 300, ServiceColor::red());
+        grid.contribute(2, 2, 300, ServiceColor::blue());
+        assert_eq!(grid.regime(2, 2), Regime::Liquid);
+
+        grid.contribute(2, 2, 500, ServiceColor::green());
+        assert_eq!(grid.regime(2, 2), Regime::Solid);
+    }
+
+    #[test]
+    fn test_regime_hysteresis() {
+        let thresholds = RegimeThresholds::with_margin(1000, 500, 100);
+        let grid = IsotopeGrid::with_regimes(10, 10, 50, thresholds);
+
+        grid.contribute(2, 2, 1050, ServiceColor::red());
+        assert_eq!(grid.regime(2, 2), Regime::Solid);
+
+        // Below the enter boundary, above the exit boundary
+        grid.apply_decay();
+        grid.apply_decay();
+        assert_eq!(grid.density(2, 2), 950);
+        assert_eq!(grid.regime(2, 2), Regime::Solid);
+
+        grid.apply_decay();
+        assert_eq!(grid.regime(2, 2), Regime::Liquid);
+    }
+
+    #[test]
+    fn test_degenerate_grids_diffuse() {
+        // Used to underflow on `h - 1`
+        for (w, h) in [(0, 0), (1, 0), (0, 1), (1, 1), (3, 1)] {
+            let grid = IsotopeGrid::new(w, h, 1, 1000, 500);
+            grid.diffuse();
+            grid.apply_decay();
+        }
+        assert!(matches!(
+            IsotopeGrid::try_new(0, 0, 1, 1000, 500),
+            Err(TesError::ZeroDimension)
+        ));
+    }
 }
@@ -25,9 +25,63 @@ mod shape;
 mod grid;
 mod substrate;
 mod isotope;
+mod relation;
+mod footprint;
+mod field;
+mod concurrent;
+mod driver;
+mod event;
+mod stats;
+mod accounting;
+mod config;
+mod error;
+mod snapshot;
+mod topology;
+mod waiting;
+mod slitscan;
+#[cfg(any(feature = "wasm", feature = "render"))]
+mod palette;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "tower")]
+pub mod admission;
+#[cfg(feature = "shm")]
+mod shared;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "render")]
+pub mod render;
 
 pub use space::Space;
 pub use shape::Shape;
-pub use grid::DensityGrid;
+pub use grid::{DecayModel, DensityGrid, Regime, RegimeThresholds};
 pub use substrate::Substrate;
 pub use isotope::{IsotopeGrid, ServiceColor};
+pub use relation::Relation;
+pub use footprint::{Footprint, Habitability};
+pub use field::TraceField;
+pub use concurrent::ConcurrentSubstrate;
+pub use event::{Event, Observer};
+pub use driver::{Clock, DriverHandle, ManualClock, SystemClock, Tick, TickDriver};
+pub use stats::FieldStats;
+pub use accounting::{RejectionHeatmap, SpawnCounts, SpawnStats};
+pub use snapshot::{Snapshot, SnapshotError, SNAPSHOT_VERSION};
+#[cfg(feature = "metrics")]
+pub use metrics::MetricsServer;
+pub use topology::{Boundary, Topology};
+pub use config::{ConfigError, RegimeConfig, SpaceBuilder, SpaceConfig};
+pub use error::TesError;
+pub use waiting::{Acquire, AcquireError, WaitingRoom};
+pub use slitscan::{Slit, SlitScan};
+#[cfg(feature = "tower")]
+pub use admission::{AdmissionLayer, Overloaded};
+#[cfg(feature = "shm")]
+pub use shared::{SharedGrid, SHARED_VERSION};
+#[cfg(feature = "render")]
+pub use render::Frame;
@@ -0,0 +1,407 @@
+//! Metrics - OpenMetrics text exporter
+//!
+//! Renders field and substrate statistics in the OpenMetrics text format
+//! and serves them from a tiny embedded HTTP endpoint, so services running
+//! TES can be scraped by Prometheus.
+//!
+//! Only available with the `metrics` feature. No dependencies beyond std.
+
+use std::fmt::{Display, Write as _};
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::concurrent::ConcurrentSubstrate;
+use crate::field::TraceField;
+use crate::grid::Regime;
+use crate::stats::FieldStats;
+use crate::substrate::Substrate;
+
+/// Content type of the OpenMetrics text format.
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Density quantiles reported for every field.
+const QUANTILES: [f64; 4] = [0.5, 0.9, 0.99, 1.0];
+
+/// Accumulates metric families into an OpenMetrics exposition.
+struct Encoder {
+    out: String,
+}
+
+impl Encoder {
+    fn new() -> Self {
+        Self { out: String::new() }
+    }
+
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (i, (key, val)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{}=\"{}\"", key, val);
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {}", value);
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: impl Display) {
+        self.family(name, "gauge", help);
+        self.sample(name, &[], value);
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.family(name, "counter", help);
+        self.sample(&format!("{}_total", name), &[], value);
+    }
+
+    fn field<F: TraceField + ?Sized>(&mut self, field: &F) {
+        let stats = FieldStats::of(field);
+        let (w, h) = field.dimensions();
+
+        let mut densities = Vec::with_capacity(w * h);
+        let mut channels: Option<[u64; 3]> = None;
+        for y in 0..h {
+            for x in 0..w {
+                densities.push(field.density(x, y));
+                if let Some((r, g, b)) = field.channels(x, y) {
+                    let mass = channels.get_or_insert([0; 3]);
+                    mass[0] += r as u64;
+                    mass[1] += g as u64;
+                    mass[2] += b as u64;
+                }
+            }
+        }
+        densities.sort_unstable();
+
+        self.family(
+            "tes_cell_density",
+            "summary",
+            "Trace density per cell (fixed-point, 1000 = 1.0).",
+        );
+        for q in QUANTILES {
+            let value = quantile(&densities, q);
+            self.sample("tes_cell_density", &[("quantile", &q.to_string())], value);
+        }
+        self.sample("tes_cell_density_sum", &[], stats.total_mass);
+        self.sample("tes_cell_density_count", &[], stats.cells);
+
+        self.gauge(
+            "tes_trace_mass",
+            "Total trace density of the field.",
+            stats.total_mass,
+        );
+        self.gauge(
+            "tes_field_entropy_bits",
+            "Shannon entropy of the normalized field.",
+            stats.entropy,
+        );
+
+        self.family("tes_regime_cells", "gauge", "Cells per regime.");
+        for (regime, count) in [
+            ("solid", stats.solid),
+            ("liquid", stats.liquid),
+            ("gas", stats.gas),
+        ] {
+            self.sample("tes_regime_cells", &[("regime", regime)], count);
+        }
+        self.gauge(
+            "tes_regime_solid_ratio",
+            "Fraction of cells in Solid regime.",
+            stats.solid_pct() / 100.0,
+        );
+        self.gauge(
+            "tes_phase_dominant",
+            "Dominant regime (0 = gas, 1 = liquid, 2 = solid).",
+            match stats.phase() {
+                Regime::Gas => 0,
+                Regime::Liquid => 1,
+                Regime::Solid => 2,
+            },
+        );
+
+        if let Some([r, g, b]) = channels {
+            self.family(
+                "tes_channel_mass",
+                "gauge",
+                "Total trace density per isotope channel.",
+            );
+            for (channel, mass) in [("r", r), ("g", g), ("b", b)] {
+                self.sample("tes_channel_mass", &[("channel", channel)], mass);
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str("# EOF\n");
+        self.out
+    }
+}
+
+/// Nearest-rank quantile of sorted values.
+fn quantile(sorted: &[u32], q: f64) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Render field statistics as an OpenMetrics exposition.
+pub fn encode_field<F: TraceField + ?Sized>(field: &F) -> String {
+    let mut enc = Encoder::new();
+    enc.field(field);
+    enc.finish()
+}
+
+impl<P, F: TraceField> Substrate<P, F> {
+    /// Render field, shape and spawn statistics as an OpenMetrics exposition.
+    pub fn encode_metrics(&self) -> String {
+        let mut enc = Encoder::new();
+        enc.field(self.space().field());
+        enc.gauge("tes_shapes", "Living shapes.", self.shape_count());
+        enc.counter("tes_ticks", "Ticks run.", self.tick_count());
+
+        let spawns = self.spawn_stats().total();
+        enc.family("tes_spawns", "counter", "Spawn attempts by outcome (A1).");
+        enc.sample(
+            "tes_spawns_total",
+            &[("outcome", "accepted")],
+            spawns.accepted,
+        );
+        enc.sample(
+            "tes_spawns_total",
+            &[("outcome", "rejected")],
+            spawns.rejected,
+        );
+        enc.finish()
+    }
+}
+
+impl<P, F: TraceField> ConcurrentSubstrate<P, F> {
+    /// Render field and shape statistics as an OpenMetrics exposition.
+    pub fn encode_metrics(&self) -> String {
+        let mut enc = Encoder::new();
+        enc.field(self.space().field());
+        enc.gauge("tes_shapes", "Living shapes.", self.shape_count());
+        enc.counter("tes_ticks", "Ticks run.", self.tick_count());
+        enc.finish()
+    }
+}
+
+/// Minimal HTTP endpoint serving an OpenMetrics exposition.
+///
+/// Every `GET /metrics` calls `source` and returns its output; other paths
+/// get a 404. Requests are served one at a time on a background thread.
+///
+/// Dropping the server stops it and waits for its thread.
+pub struct MetricsServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    /// Bind to `addr` and start serving.
+    ///
+    /// Bind to port 0 to pick a free port, see
+    /// [`local_addr`](MetricsServer::local_addr).
+    pub fn bind<A, S>(addr: A, source: S) -> io::Result<Self>
+    where
+        A: ToSocketAddrs,
+        S: Fn() -> String + Send + 'static,
+    {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stopped = Arc::clone(&stop);
+        let thread = thread::Builder::new()
+            .name("tes-metrics".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // A misbehaving client only loses its own response
+                        let _ = serve(stream, &source);
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stop serving and wait for the server thread.
+    pub fn shutdown(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            // Wake the blocking accept
+            let _ = TcpStream::connect(wake_addr(self.addr));
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+/// Connectable address for a listener (unspecified binds map to loopback).
+fn wake_addr(mut addr: SocketAddr) -> SocketAddr {
+    if addr.ip().is_unspecified() {
+        match addr {
+            SocketAddr::V4(_) => addr.set_ip(Ipv4Addr::LOCALHOST.into()),
+            SocketAddr::V6(_) => addr.set_ip(Ipv6Addr::LOCALHOST.into()),
+        }
+    }
+    addr
+}
+
+/// Answer a single HTTP/1.x request.
+fn serve(mut stream: TcpStream, source: &impl Fn() -> String) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    // Read the request head (bounded); the body, if any, is ignored
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < 8192 {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("");
+    let path = request_line.next().unwrap_or("");
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => ("200 OK", CONTENT_TYPE, source()),
+        ("GET", _) => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n".to_string(),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isotope::{IsotopeGrid, ServiceColor};
+    use std::sync::Mutex;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let sorted: Vec<u32> = (1..=100).collect();
+        assert_eq!(quantile(&sorted, 0.5), 50);
+        assert_eq!(quantile(&sorted, 0.99), 99);
+        assert_eq!(quantile(&sorted, 1.0), 100);
+        assert_eq!(quantile(&[], 0.5), 0);
+    }
+
+    #[test]
+    fn test_substrate_exposition() {
+        let mut sub = Substrate::new(4, 4, 0, 50);
+        sub.spawn(1, 1, 10, 60).unwrap();
+        sub.tick();
+        assert!(sub.spawn(1, 1, 10, 60).is_none());
+
+        let text = sub.encode_metrics();
+        assert!(text.contains("# TYPE tes_cell_density summary\n"));
+        assert!(text.contains("tes_cell_density{quantile=\"1\"} 60\n"));
+        assert!(text.contains("tes_cell_density_count 16\n"));
+        assert!(text.contains("tes_regime_cells{regime=\"gas\"} 15\n"));
+        assert!(text.contains("tes_shapes 1\n"));
+        assert!(text.contains("tes_ticks_total 1\n"));
+        assert!(text.contains("tes_spawns_total{outcome=\"accepted\"} 1\n"));
+        assert!(text.contains("tes_spawns_total{outcome=\"rejected\"} 1\n"));
+        assert!(!text.contains("tes_channel_mass"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn test_channel_mass() {
+        let grid = IsotopeGrid::new(4, 4, 0, 1000, 500);
+        grid.contribute(0, 0, 300, ServiceColor::red());
+        grid.contribute(2, 3, 200, ServiceColor::blue());
+
+        let text = encode_field(&grid);
+        assert!(text.contains("tes_channel_mass{channel=\"r\"} 300\n"));
+        assert!(text.contains("tes_channel_mass{channel=\"g\"} 0\n"));
+        assert!(text.contains("tes_channel_mass{channel=\"b\"} 200\n"));
+        assert!(text.contains("tes_trace_mass 500\n"));
+    }
+
+    #[test]
+    fn test_server_on_localhost() {
+        let sub = Arc::new(Mutex::new(Substrate::<()>::new(4, 4, 0, 1000)));
+        let source = Arc::clone(&sub);
+        let server = MetricsServer::bind("127.0.0.1:0", move || {
+            source.lock().unwrap().encode_metrics()
+        })
+        .unwrap();
+        let addr = server.local_addr();
+
+        sub.lock().unwrap().spawn(2, 2, 5, 10).unwrap();
+        let response = get(addr, "/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.contains("tes_shapes 1\n"));
+        assert!(response.ends_with("# EOF\n"));
+
+        assert!(get(addr, "/").starts_with("HTTP/1.1 404"));
+
+        server.shutdown();
+        assert!(TcpStream::connect(addr).is_err());
+    }
+}
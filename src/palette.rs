@@ -0,0 +1,125 @@
+//! Palette - how the observation plane colors a field
+//!
+//! Every frame producer uses the same mapping as `tes-viz`:
+//!
+//! - magenta where a rejection flashed (A1 backpressure made visible)
+//! - white walls where density reached the habitability threshold
+//! - below it, a danger ramp: each channel scales linearly up to full
+//!   brightness at the threshold
+//!
+//! Scalar fields have no channels and ramp in grayscale.
+
+use crate::accounting::RejectionHeatmap;
+use crate::field::TraceField;
+use crate::slitscan::SlitScan;
+
+/// Not yet recorded (slit-scan history).
+pub(crate) const EMPTY: [u8; 4] = [0, 0, 0, 255];
+
+/// Flash color of a rejected contribution.
+pub(crate) const FLASH: [u8; 4] = [255, 0, 255, 255];
+
+/// Saturated (uninhabitable) cell.
+pub(crate) const WALL: [u8; 4] = [255, 255, 255, 255];
+
+/// RGBA of one cell.
+pub(crate) fn pixel(rgb: (u32, u32, u32), density: u32, threshold: u32, flash: u8) -> [u8; 4] {
+    if flash > 0 {
+        return FLASH;
+    }
+    if density >= threshold {
+        return WALL;
+    }
+    let scale = |v: u32| (u64::from(v) * 255 / u64::from(threshold)).min(255) as u8;
+    [scale(rgb.0), scale(rgb.1), scale(rgb.2), 255]
+}
+
+/// Render `field` as row-major RGBA into `out`, which is resized to fit.
+pub(crate) fn frame<F: TraceField + ?Sized>(
+    field: &F,
+    threshold: u32,
+    flashes: Option<&RejectionHeatmap>,
+    out: &mut Vec<u8>,
+) {
+    let (width, height) = field.dimensions();
+    out.clear();
+    out.reserve(width * height * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let density = field.density(x, y);
+            let rgb = field.channels(x, y).unwrap_or((density, density, density));
+            let flash = flashes.map_or(0, |f| f.intensity(x, y));
+            out.extend_from_slice(&pixel(rgb, density, threshold, flash));
+        }
+    }
+}
+
+/// Render the cuts of `scan` as RGBA into `out`: one column per cut, the
+/// newest on the right edge, one row per slit cell.
+pub(crate) fn slit_scan(scan: &SlitScan, threshold: u32, out: &mut Vec<u8>) {
+    let (depth, len) = (scan.depth(), scan.positions().len());
+    out.clear();
+    out.reserve(depth * len * 4);
+    for i in 0..len {
+        for x in 0..depth {
+            let age = depth - 1 - x;
+            let rgba = match scan.density(age, i) {
+                Some(d) => pixel(scan.channels(age, i).unwrap_or((d, d, d)), d, threshold, 0),
+                None => EMPTY,
+            };
+            out.extend_from_slice(&rgba);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::DensityGrid;
+    use crate::isotope::{IsotopeGrid, ServiceColor};
+    use crate::slitscan::Slit;
+
+    #[test]
+    fn test_palette() {
+        let grid = IsotopeGrid::new(3, 1, 0, 1000, 500);
+        grid.contribute(0, 0, 500, ServiceColor::red());
+        grid.contribute(1, 0, 1000, ServiceColor::green());
+        grid.contribute(2, 0, 100, ServiceColor::blue());
+        let mut flashes = RejectionHeatmap::new(3, 1, u8::MAX);
+        flashes.record(2, 0);
+
+        let mut out = Vec::new();
+        frame(&grid, 1000, Some(&flashes), &mut out);
+        assert_eq!(out, [[127, 0, 0, 255], WALL, FLASH].concat());
+
+        flashes.fade();
+        frame(&grid, 1000, Some(&flashes), &mut out);
+        assert_eq!(&out[8..], &[0, 0, 25, 255]);
+    }
+
+    #[test]
+    fn test_slit_scan_playhead_on_the_right() {
+        let grid = DensityGrid::new(2, 2, 0, 100, 50);
+        let mut scan = SlitScan::new(&grid, Slit::Column(0), 3);
+        scan.record(&grid);
+        grid.contribute(0, 1, 100);
+        scan.record(&grid);
+
+        let mut out = Vec::new();
+        slit_scan(&scan, 100, &mut out);
+        // 3 cuts wide, 2 cells tall: empty, first cut, newest cut
+        let top = [EMPTY, [0, 0, 0, 255], [0, 0, 0, 255]].concat();
+        let bottom = [EMPTY, [0, 0, 0, 255], WALL].concat();
+        assert_eq!(out, [top, bottom].concat());
+    }
+
+    #[test]
+    fn test_scalar_field_is_grayscale() {
+        let grid = DensityGrid::new(2, 1, 0, 1000, 500);
+        grid.contribute(1, 0, 200);
+
+        let mut out = vec![9; 3];
+        frame(&grid, 400, None, &mut out);
+        assert_eq!(out, [0, 0, 0, 255, 127, 127, 127, 255]);
+    }
+}
@@ -0,0 +1,496 @@
+//! Python - PyO3 module `tes` for research notebooks (feature `python`)
+//!
+//! Exposes [`Substrate`], [`DensityGrid`], [`IsotopeGrid`] and
+//! [`ServiceColor`] under their Rust names. Density maps are returned as
+//! read-only numpy arrays that view the field's memory directly: no copy is
+//! made and the array follows later ticks in place. Each array keeps its
+//! grid or substrate alive. Regimes are reported as `"solid"`, `"liquid"`
+//! and `"gas"`; invalid parameters raise `ValueError`.
+//!
+//! Build the extension with maturin (see `pyproject.toml`):
+//!
+//! ```text
+//! maturin develop --release
+//! ```
+
+use std::sync::atomic::AtomicU32;
+
+use numpy::ndarray::{ArrayView, Dimension, Ix2, Ix3, ShapeBuilder};
+use numpy::{PyArray, PyArray2, PyArray3};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::error::TesError;
+use crate::grid::{DensityGrid, Regime, RegimeThresholds};
+use crate::isotope::{IsotopeGrid, ServiceColor};
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::substrate::Substrate;
+
+impl From<TesError> for PyErr {
+    fn from(e: TesError) -> Self {
+        match e {
+            TesError::Config(e) => PyValueError::new_err(e.to_string()),
+            TesError::Snapshot(e) => e.into(),
+            TesError::Io(e) => e.into(),
+            e => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+impl From<SnapshotError> for PyErr {
+    fn from(e: SnapshotError) -> Self {
+        PyValueError::new_err(e.to_string())
+    }
+}
+
+fn regime_name(regime: Regime) -> &'static str {
+    match regime {
+        Regime::Solid => "solid",
+        Regime::Liquid => "liquid",
+        Regime::Gas => "gas",
+    }
+}
+
+/// View `words` as an array of `shape`, with element `strides` starting at
+/// `offset`.
+///
+/// # Safety
+///
+/// The view must stay within `words`, and the cells must not be written
+/// while it is read. Exported arrays outlive the call, so this holds only
+/// because reads and writes both need the GIL (see [`export`]).
+unsafe fn view<D: Dimension>(
+    words: &[AtomicU32],
+    offset: usize,
+    shape: D,
+    strides: D,
+) -> ArrayView<'_, u32, D> {
+    ArrayView::from_shape_ptr(shape.strides(strides), words.as_ptr().add(offset).cast())
+}
+
+/// `(height, width)` densities of a [`DensityGrid`].
+fn density_view(grid: &DensityGrid) -> ArrayView<'_, u32, Ix2> {
+    let (w, h) = grid.dimensions();
+    // SAFETY: one word per cell, row-major
+    unsafe { view(grid.cells(), 0, Ix2(h, w), Ix2(w, 1)) }
+}
+
+/// `(height, width)` totals of an [`IsotopeGrid`].
+fn isotope_density_view(grid: &IsotopeGrid) -> ArrayView<'_, u32, Ix2> {
+    let (w, h) = grid.dimensions();
+    // SAFETY: total is the fourth of four words per cell
+    unsafe { view(grid.words(), 3, Ix2(h, w), Ix2(4 * w, 4)) }
+}
+
+/// `(height, width, 3)` channels of an [`IsotopeGrid`].
+fn isotope_rgb_view(grid: &IsotopeGrid) -> ArrayView<'_, u32, Ix3> {
+    let (w, h) = grid.dimensions();
+    // SAFETY: r, g, b are the first three of four words per cell
+    unsafe { view(grid.words(), 0, Ix3(h, w, 3), Ix3(4 * w, 4, 1)) }
+}
+
+/// Export `view` as a read-only numpy array owned by `owner`.
+fn export<'py, D: Dimension>(
+    view: ArrayView<'_, u32, D>,
+    owner: Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyArray<u32, D>>> {
+    // SAFETY: `owner` keeps the memory behind `view` alive and never
+    // reallocates it. The array outlives this call, and numpy reads it
+    // non-atomically: that is sound only because every numpy read and
+    // every grid write (all methods, none releases the GIL) hold the GIL,
+    // which the module keeps on free-threaded builds (`gil_used`).
+    let array = unsafe { PyArray::borrow_from_array(&view, owner) };
+    array.call_method1("setflags", (false,))?;
+    Ok(array)
+}
+
+/// Service color signature (fixed point, 1000 = 1.0 per channel).
+#[pyclass(name = "ServiceColor", module = "tes", frozen, eq, hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PyServiceColor(ServiceColor);
+
+#[pymethods]
+impl PyServiceColor {
+    #[new]
+    fn new(r: u32, g: u32, b: u32) -> Self {
+        Self(ServiceColor { r, g, b })
+    }
+
+    /// Deterministic color for a service name.
+    #[staticmethod]
+    fn from_name(name: &str) -> Self {
+        Self(ServiceColor::from_name(name))
+    }
+
+    #[staticmethod]
+    fn neutral() -> Self {
+        Self(ServiceColor::neutral())
+    }
+
+    #[staticmethod]
+    fn red() -> Self {
+        Self(ServiceColor::red())
+    }
+
+    #[staticmethod]
+    fn green() -> Self {
+        Self(ServiceColor::green())
+    }
+
+    #[staticmethod]
+    fn blue() -> Self {
+        Self(ServiceColor::blue())
+    }
+
+    #[getter]
+    fn r(&self) -> u32 {
+        self.0.r
+    }
+
+    #[getter]
+    fn g(&self) -> u32 {
+        self.0.g
+    }
+
+    #[getter]
+    fn b(&self) -> u32 {
+        self.0.b
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ServiceColor({}, {}, {})", self.0.r, self.0.g, self.0.b)
+    }
+}
+
+/// Scalar density grid.
+#[pyclass(name = "DensityGrid", module = "tes", frozen)]
+pub struct PyDensityGrid(DensityGrid);
+
+#[pymethods]
+impl PyDensityGrid {
+    #[new]
+    fn new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        solid_threshold: u32,
+        liquid_threshold: u32,
+    ) -> PyResult<Self> {
+        let grid =
+            DensityGrid::try_new(width, height, decay_rate, solid_threshold, liquid_threshold)?;
+        Ok(Self(grid))
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.0.dimensions().0
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.0.dimensions().1
+    }
+
+    fn contribute(&self, x: usize, y: usize, amount: u32) {
+        self.0.contribute(x, y, amount);
+    }
+
+    /// Contribute only if density is below `threshold` (A1).
+    fn try_contribute(&self, x: usize, y: usize, amount: u32, threshold: u32) -> bool {
+        self.0.try_contribute(x, y, amount, threshold)
+    }
+
+    fn density(&self, x: usize, y: usize) -> u32 {
+        self.0.density(x, y)
+    }
+
+    fn regime(&self, x: usize, y: usize) -> &'static str {
+        regime_name(self.0.regime(x, y))
+    }
+
+    fn apply_decay(&self) {
+        self.0.apply_decay();
+    }
+
+    fn diffuse(&self) {
+        self.0.diffuse();
+    }
+
+    /// Live `(height, width)` uint32 view of the densities.
+    fn density_map<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray2<u32>>> {
+        export(density_view(&slf.get().0), slf.clone().into_any())
+    }
+
+    fn to_snapshot<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.to_snapshot())
+    }
+
+    #[staticmethod]
+    fn from_snapshot(data: &[u8]) -> PyResult<Self> {
+        Ok(Self(DensityGrid::from_snapshot(data)?))
+    }
+}
+
+/// RGB isotope grid.
+#[pyclass(name = "IsotopeGrid", module = "tes", frozen)]
+pub struct PyIsotopeGrid(IsotopeGrid);
+
+#[pymethods]
+impl PyIsotopeGrid {
+    #[new]
+    fn new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        solid_threshold: u32,
+        liquid_threshold: u32,
+    ) -> PyResult<Self> {
+        let grid =
+            IsotopeGrid::try_new(width, height, decay_rate, solid_threshold, liquid_threshold)?;
+        Ok(Self(grid))
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.0.dimensions().0
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.0.dimensions().1
+    }
+
+    fn contribute(&self, x: usize, y: usize, amount: u32, color: PyServiceColor) {
+        self.0.contribute(x, y, amount, color.0);
+    }
+
+    /// Contribute only if total density is below `threshold` (A1).
+    fn try_contribute(
+        &self,
+        x: usize,
+        y: usize,
+        amount: u32,
+        color: PyServiceColor,
+        threshold: u32,
+    ) -> bool {
+        self.0.try_contribute(x, y, amount, color.0, threshold)
+    }
+
+    fn density(&self, x: usize, y: usize) -> u32 {
+        self.0.density(x, y)
+    }
+
+    fn rgb(&self, x: usize, y: usize) -> (u32, u32, u32) {
+        self.0.rgb(x, y)
+    }
+
+    fn regime(&self, x: usize, y: usize) -> &'static str {
+        regime_name(self.0.regime(x, y))
+    }
+
+    fn apply_decay(&self) {
+        self.0.apply_decay();
+    }
+
+    fn diffuse(&self) {
+        self.0.diffuse();
+    }
+
+    /// Live `(height, width)` uint32 view of the total densities.
+    fn density_map<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray2<u32>>> {
+        export(isotope_density_view(&slf.get().0), slf.clone().into_any())
+    }
+
+    /// Live `(height, width, 3)` uint32 view of the R, G, B channels.
+    fn rgb_map<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray3<u32>>> {
+        export(isotope_rgb_view(&slf.get().0), slf.clone().into_any())
+    }
+
+    fn to_snapshot<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.to_snapshot())
+    }
+
+    #[staticmethod]
+    fn from_snapshot(data: &[u8]) -> PyResult<Self> {
+        Ok(Self(IsotopeGrid::from_snapshot(data)?))
+    }
+}
+
+/// Substrate on a scalar or an isotope field.
+enum Field {
+    Density(Substrate<(), DensityGrid>),
+    Isotope(Substrate<(), IsotopeGrid>),
+}
+
+/// Run `$body` with `$s` bound to the substrate, whatever its field.
+macro_rules! with_substrate {
+    ($field:expr, $s:ident => $body:expr) => {
+        match $field {
+            Field::Density($s) => $body,
+            Field::Isotope($s) => $body,
+        }
+    };
+}
+
+/// Shapes living in a space. With `isotopes=True` the field is an
+/// [`IsotopeGrid`] and shapes leave trace in their color.
+#[pyclass(name = "Substrate", module = "tes", unsendable)]
+pub struct PySubstrate(Field);
+
+#[pymethods]
+impl PySubstrate {
+    /// Shapes are admitted where density is below `threshold`; the liquid
+    /// boundary is `threshold / 2`.
+    #[new]
+    #[pyo3(signature = (width, height, decay_rate, threshold, isotopes = false))]
+    fn new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        threshold: u32,
+        isotopes: bool,
+    ) -> PyResult<Self> {
+        let thresholds = RegimeThresholds::new(threshold, threshold / 2);
+        let field = if isotopes {
+            Field::Isotope(Substrate::try_with_isotopes(
+                width, height, decay_rate, thresholds,
+            )?)
+        } else {
+            Field::Density(Substrate::try_new(width, height, decay_rate, thresholds)?)
+        };
+        Ok(Self(field))
+    }
+
+    /// Spawn a shape; returns its ID, or None if the position is not
+    /// habitable (A1).
+    #[pyo3(signature = (x, y, lifetime, contribution, color = None))]
+    fn spawn(
+        &mut self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+        color: Option<PyServiceColor>,
+    ) -> Option<u64> {
+        let color = color.map_or_else(ServiceColor::neutral, |c| c.0);
+        with_substrate!(&mut self.0, s => s.spawn_colored(x, y, lifetime, contribution, color))
+    }
+
+    fn tick(&mut self) {
+        with_substrate!(&mut self.0, s => s.tick())
+    }
+
+    fn run(&mut self, ticks: u64) {
+        with_substrate!(&mut self.0, s => s.run(ticks))
+    }
+
+    #[getter]
+    fn tick_count(&self) -> u64 {
+        with_substrate!(&self.0, s => s.tick_count())
+    }
+
+    #[getter]
+    fn shape_count(&self) -> usize {
+        with_substrate!(&self.0, s => s.shape_count())
+    }
+
+    fn density(&self, x: usize, y: usize) -> u32 {
+        with_substrate!(&self.0, s => s.space().density(x, y))
+    }
+
+    fn regime(&self, x: usize, y: usize) -> &'static str {
+        regime_name(with_substrate!(&self.0, s => s.space().regime(x, y)))
+    }
+
+    /// Live `(height, width)` uint32 view of the field's densities.
+    fn density_map<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray2<u32>>> {
+        let owner = slf.clone().into_any();
+        match &slf.borrow().0 {
+            Field::Density(s) => export(density_view(s.space().field()), owner),
+            Field::Isotope(s) => export(isotope_density_view(s.space().field()), owner),
+        }
+    }
+}
+
+/// The `tes` Python module.
+// Exported arrays alias grid memory (see `export`): keep the GIL even on
+// free-threaded CPython.
+#[pymodule(name = "tes", gil_used = true)]
+fn tes_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySubstrate>()?;
+    m.add_class::<PyDensityGrid>()?;
+    m.add_class::<PyIsotopeGrid>()?;
+    m.add_class::<PyServiceColor>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    #[test]
+    fn test_views_match_fields() {
+        let grid = DensityGrid::new(3, 2, 0, 1000, 500);
+        grid.contribute(2, 1, 70);
+        let view = density_view(&grid);
+        assert_eq!(view.dim(), (2, 3));
+        assert_eq!(view[[1, 2]], 70);
+        assert_eq!(view.sum(), 70);
+
+        let iso = IsotopeGrid::new(3, 2, 0, 1000, 500);
+        iso.contribute(1, 0, 300, ServiceColor::blue());
+        iso.contribute(2, 1, 90, ServiceColor::red());
+        let totals = isotope_density_view(&iso);
+        assert_eq!(totals[[0, 1]], iso.density(1, 0));
+        assert_eq!(totals[[1, 2]], 90);
+        let rgb = isotope_rgb_view(&iso);
+        assert_eq!(rgb.dim(), (2, 3, 3));
+        assert_eq!(
+            (rgb[[0, 1, 0]], rgb[[0, 1, 1]], rgb[[0, 1, 2]]),
+            iso.rgb(1, 0)
+        );
+        assert_eq!(rgb[[1, 2, 0]], 90);
+    }
+
+    #[test]
+    fn test_module_api() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = PyModule::new(py, "tes").unwrap();
+            tes_module(&m).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("tes", m).unwrap();
+            py.run(
+                cr#"
+sub = tes.Substrate(8, 8, 0, 100)
+assert sub.spawn(2, 2, 5, 60) == 1
+sub.run(2)
+assert sub.density(2, 2) == 120 and sub.regime(2, 2) == "solid"
+assert sub.spawn(2, 2, 5, 60) is None
+assert sub.tick_count == 2 and sub.shape_count == 1
+
+grid = tes.IsotopeGrid(4, 4, 10, 1000, 500)
+red = tes.ServiceColor.red()
+assert grid.try_contribute(1, 1, 1200, red, 1000)
+assert not grid.try_contribute(1, 1, 1, red, 1000)
+grid.apply_decay()
+assert grid.rgb(1, 1) == (1190, 0, 0)
+copy = tes.IsotopeGrid.from_snapshot(grid.to_snapshot())
+assert copy.density(1, 1) == 1190
+assert tes.ServiceColor.from_name("Auth") == tes.ServiceColor.from_name("Auth")
+
+try:
+    tes.DensityGrid(0, 4, 1, 1000, 500)
+    raise AssertionError("accepted zero width")
+except ValueError as e:
+    assert "non-zero" in str(e)
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,140 @@
+//! Relation - the relational half of A0
+//!
+//! A shape with payload only produces trace when it is related to at least
+//! one other living shape:
+//!
+//! ```text
+//! temporal_observation(s) ⟺ payload(s) ≠ ∅ ∧ ∃s': related(s, s')
+//! ```
+//!
+//! Relations are markers, not rules. An unrelated shape is not rejected;
+//! it simply leaves no trace.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::shape::Shape;
+
+/// How `related(s, s')` is evaluated each tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Relation {
+    /// Every shape is considered related (relationality is not observed).
+    #[default]
+    Unconditional,
+    /// Shapes are related when co-located within `radius` cells
+    /// (Chebyshev distance). `radius = 0` means sharing the same cell.
+    Proximity { radius: usize },
+    /// Shapes are related when they share a group tag.
+    Group,
+    /// Shapes are related only through explicit links.
+    Link,
+}
+
+impl Relation {
+    /// Evaluate relationality for all shapes, updating `Shape::related`.
+    ///
+    /// `links` are undirected pairs of shape IDs.
+    /// Complexity: O(n) expected for every variant.
+    pub(crate) fn evaluate<P>(&self, shapes: &mut [Shape<P>], links: &[(u64, u64)]) {
+        match *self {
+            Relation::Unconditional => {
+                for shape in shapes.iter_mut() {
+                    shape.related = true;
+                }
+            }
+            Relation::Proximity { radius } => relate_by_proximity(shapes, radius),
+            Relation::Group => {
+                let mut counts: HashMap<u64, usize> = HashMap::new();
+                for group in shapes.iter().filter_map(|s| s.group) {
+                    *counts.entry(group).or_default() += 1;
+                }
+                for shape in shapes.iter_mut() {
+                    shape.related = shape.group.is_some_and(|g| counts[&g] > 1);
+                }
+            }
+            Relation::Link => {
+                let linked: HashSet<u64> = links.iter().flat_map(|&(a, b)| [a, b]).collect();
+                for shape in shapes.iter_mut() {
+                    shape.related = linked.contains(&shape.id);
+                }
+            }
+        }
+    }
+}
+
+/// Bucket shapes into `(radius + 1)`-sized blocks so each shape only
+/// compares against its own and adjacent blocks.
+fn relate_by_proximity<P>(shapes: &mut [Shape<P>], radius: usize) {
+    let block = radius + 1;
+    let mut buckets: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (i, shape) in shapes.iter().enumerate() {
+        let (x, y) = shape.pos;
+        buckets.entry((x / block, y / block)).or_default().push(i);
+    }
+
+    let mut related = vec![false; shapes.len()];
+    for (i, shape) in shapes.iter().enumerate() {
+        let (x, y) = shape.pos;
+        let (bx, by) = (x / block, y / block);
+        'search: for nby in by.saturating_sub(1)..=by + 1 {
+            for nbx in bx.saturating_sub(1)..=bx + 1 {
+                let Some(bucket) = buckets.get(&(nbx, nby)) else {
+                    continue;
+                };
+                for &j in bucket {
+                    let (ox, oy) = shapes[j].pos;
+                    if j != i && x.abs_diff(ox) <= radius && y.abs_diff(oy) <= radius {
+                        related[i] = true;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+
+    for (shape, related) in shapes.iter_mut().zip(related) {
+        shape.related = related;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape_at(id: u64, x: usize, y: usize) -> Shape {
+        Shape::new(id, x, y, 100, 10, 1.0, 1)
+    }
+
+    #[test]
+    fn test_proximity_radius() {
+        let mut shapes = vec![shape_at(1, 0, 0), shape_at(2, 2, 2), shape_at(3, 9, 9)];
+
+        Relation::Proximity { radius: 1 }.evaluate(&mut shapes, &[]);
+        assert!(shapes.iter().all(|s| !s.related));
+
+        Relation::Proximity { radius: 2 }.evaluate(&mut shapes, &[]);
+        assert!(shapes[0].related && shapes[1].related);
+        assert!(!shapes[2].related, "Isolated shape must not be related");
+    }
+
+    #[test]
+    fn test_group_tags() {
+        let mut shapes = vec![shape_at(1, 0, 0), shape_at(2, 5, 5), shape_at(3, 9, 9)];
+        shapes[0].group = Some(7);
+        shapes[1].group = Some(7);
+        shapes[2].group = Some(8);
+
+        Relation::Group.evaluate(&mut shapes, &[]);
+        assert!(shapes[0].related && shapes[1].related);
+        assert!(!shapes[2].related, "Single-member group is not a relation");
+    }
+
+    #[test]
+    fn test_explicit_links() {
+        let mut shapes = vec![shape_at(1, 0, 0), shape_at(2, 5, 5), shape_at(3, 9, 9)];
+
+        Relation::Link.evaluate(&mut shapes, &[(1, 3)]);
+        assert!(shapes[0].related && shapes[2].related);
+        assert!(!shapes[1].related);
+    }
+}
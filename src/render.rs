@@ -0,0 +1,229 @@
+//! Render - headless CPU renderer
+//!
+//! Turns any [`TraceField`] into an RGBA [`Frame`] with the palette of
+//! `tes-viz` (danger ramp, white saturation walls, magenta rejection
+//! flashes) and writes it as PNG. Needs no window, GPU or event loop, so
+//! CI jobs and reports can include field images.
+//!
+//! Only available with the `render` feature.
+//!
+//! ```
+//! use tes::render::Frame;
+//! use tes::{IsotopeGrid, ServiceColor};
+//!
+//! let grid = IsotopeGrid::new(64, 64, 5, 1000, 500);
+//! grid.contribute(10, 10, 800, ServiceColor::from_name("AuthService"));
+//!
+//! let frame = Frame::of(&grid, 1000);
+//! let mut png = Vec::new();
+//! frame.write_png(&mut png).unwrap();
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::accounting::RejectionHeatmap;
+use crate::error::TesError;
+use crate::field::TraceField;
+use crate::palette;
+use crate::slitscan::SlitScan;
+
+/// Row-major RGBA8 image of a field, one pixel per cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Render `field`; cells at or above `threshold` are walls.
+    pub fn of<F: TraceField + ?Sized>(field: &F, threshold: u32) -> Self {
+        Self::render(field, threshold, None)
+    }
+
+    /// Render `field` with rejection flashes from `flashes` on top.
+    pub fn with_flashes<F: TraceField + ?Sized>(
+        field: &F,
+        threshold: u32,
+        flashes: &RejectionHeatmap,
+    ) -> Self {
+        Self::render(field, threshold, Some(flashes))
+    }
+
+    fn render<F: TraceField + ?Sized>(
+        field: &F,
+        threshold: u32,
+        flashes: Option<&RejectionHeatmap>,
+    ) -> Self {
+        let mut frame = Self {
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+        };
+        frame.redraw(field, threshold, flashes);
+        frame
+    }
+
+    /// Render `field` again, reusing the pixel buffer (e.g. every tick).
+    pub fn redraw<F: TraceField + ?Sized>(
+        &mut self,
+        field: &F,
+        threshold: u32,
+        flashes: Option<&RejectionHeatmap>,
+    ) {
+        (self.width, self.height) = field.dimensions();
+        palette::frame(field, threshold, flashes, &mut self.pixels);
+    }
+
+    /// Render the temporal flux timeline of `scan`: one column per recorded
+    /// cut, oldest left and newest at the right edge, one row per slit
+    /// cell. Columns not yet recorded are black.
+    pub fn slit_scan(scan: &SlitScan, threshold: u32) -> Self {
+        let mut frame = Self {
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+        };
+        frame.redraw_slit_scan(scan, threshold);
+        frame
+    }
+
+    /// Render `scan` again, reusing the pixel buffer.
+    pub fn redraw_slit_scan(&mut self, scan: &SlitScan, threshold: u32) {
+        (self.width, self.height) = (scan.depth(), scan.positions().len());
+        palette::slit_scan(scan, threshold, &mut self.pixels);
+    }
+
+    /// Get dimensions in pixels.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get the RGBA bytes, row-major.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Take the RGBA bytes.
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Encode as an 8-bit RGBA PNG; an empty frame is rejected.
+    pub fn write_png<W: Write>(&self, out: W) -> Result<(), TesError> {
+        let too_large = || TesError::TooLarge {
+            width: self.width,
+            height: self.height,
+        };
+        if self.width == 0 || self.height == 0 {
+            return Err(TesError::ZeroDimension);
+        }
+        let width = u32::try_from(self.width).map_err(|_| too_large())?;
+        let height = u32::try_from(self.height).map_err(|_| too_large())?;
+
+        let mut encoder = png::Encoder::new(out, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(encoding)?;
+        writer.write_image_data(&self.pixels).map_err(encoding)?;
+        writer.finish().map_err(encoding)
+    }
+
+    /// Write a PNG file at `path`.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), TesError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_png(&mut out)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn encoding(e: png::EncodingError) -> TesError {
+    match e {
+        png::EncodingError::IoError(e) => TesError::Io(e),
+        e => TesError::Io(io::Error::other(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::DensityGrid;
+    use crate::isotope::{IsotopeGrid, ServiceColor};
+    use crate::slitscan::Slit;
+
+    fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = png::Decoder::new(io::Cursor::new(png)).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        buf.truncate(info.buffer_size());
+        (info.width, info.height, buf)
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let grid = IsotopeGrid::new(3, 2, 0, 1000, 500);
+        grid.contribute(0, 0, 1000, ServiceColor::red());
+        grid.contribute(2, 1, 400, ServiceColor::green());
+        let mut flashes = RejectionHeatmap::new(3, 2, u8::MAX);
+        flashes.record(1, 0);
+
+        let frame = Frame::with_flashes(&grid, 1000, &flashes);
+        assert_eq!(frame.dimensions(), (3, 2));
+        assert_eq!(
+            &frame.pixels()[..8],
+            &[255, 255, 255, 255, 255, 0, 255, 255]
+        );
+        assert_eq!(&frame.pixels()[20..], &[0, 102, 0, 255]);
+
+        let mut png = Vec::new();
+        frame.write_png(&mut png).unwrap();
+        assert_eq!(decode(&png), (3, 2, frame.into_pixels()));
+    }
+
+    #[test]
+    fn test_redraw_and_save() {
+        let grid = DensityGrid::new(4, 4, 100, 1000, 500);
+        grid.contribute(1, 1, 300);
+        let mut frame = Frame::of(&grid, 1000);
+        assert_eq!(&frame.pixels()[20..24], &[76, 76, 76, 255]);
+
+        grid.apply_decay();
+        frame.redraw(&grid, 1000, None);
+        assert_eq!(&frame.pixels()[20..24], &[51, 51, 51, 255]);
+
+        let path = std::env::temp_dir().join(format!("tes-render-{}.png", std::process::id()));
+        frame.save_png(&path).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(decode(&saved).2, frame.pixels());
+    }
+
+    #[test]
+    fn test_slit_scan_timeline() {
+        let grid = IsotopeGrid::new(8, 8, 0, 1000, 500);
+        let mut scan = SlitScan::new(&grid, Slit::Row(4), 16);
+        for tick in 0..20 {
+            grid.contribute(tick % 8, 4, 200, ServiceColor::red());
+            scan.record(&grid);
+        }
+
+        let mut frame = Frame::slit_scan(&scan, 1000);
+        assert_eq!(frame.dimensions(), (16, 8));
+        // Newest cut on the right edge: cell 3 got its third 200 last tick
+        let newest = |frame: &Frame, i: usize| frame.pixels()[(i * 16 + 15) * 4];
+        assert_eq!(newest(&frame, 3), 153);
+        assert_eq!(frame.pixels()[(3 * 16 + 14) * 4], 102);
+
+        grid.contribute(3, 4, 600, ServiceColor::red());
+        scan.record(&grid);
+        frame.redraw_slit_scan(&scan, 1000);
+        assert_eq!(&frame.pixels()[(3 * 16 + 15) * 4..][..4], &palette::WALL);
+
+        let mut png = Vec::new();
+        frame.write_png(&mut png).unwrap();
+        assert_eq!(decode(&png).2, frame.pixels());
+    }
+}
@@ -0,0 +1,297 @@
+//! Serde support for fields and substrates (feature `serde`)
+//!
+//! Plain types (`Regime`, `ServiceColor`, `Shape`, statistics, ...) derive
+//! `Serialize`/`Deserialize` directly. Grids hold atomics, so they go
+//! through the same state as a binary snapshot: dimensions, decay, lattice
+//! settings, regime boundaries, cell values and cell regimes (see
+//! [`crate::Snapshot`]).
+//!
+//! A substrate serializes its tick count, threshold and field; living
+//! shapes are not included (Source Amnesia).
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::field::TraceField;
+use crate::grid::{check_grid, check_threshold, DecayModel, DensityGrid, Regime, RegimeThresholds};
+use crate::isotope::IsotopeGrid;
+use crate::space::Space;
+use crate::substrate::Substrate;
+use crate::topology::{Boundary, Topology};
+
+/// Serialized form of a grid; `C` is the per-cell value.
+#[derive(Serialize, Deserialize)]
+struct GridState<C> {
+    width: usize,
+    height: usize,
+    decay_rate: u32,
+    #[serde(default)]
+    decay_model: DecayModel,
+    #[serde(default)]
+    topology: Topology,
+    #[serde(default)]
+    boundary: Boundary,
+    thresholds: RegimeThresholds,
+    cells: Vec<C>,
+    regimes: Vec<Regime>,
+}
+
+impl<C> GridState<C> {
+    /// Apply the constructors' rules before any grid is built.
+    fn check<E: serde::de::Error>(&self) -> Result<(), E> {
+        check_grid(
+            self.width,
+            self.height,
+            self.decay_model,
+            self.decay_rate,
+            self.thresholds,
+        )
+        .map_err(E::custom)?;
+        let expected = self
+            .width
+            .checked_mul(self.height)
+            .ok_or_else(|| E::custom("grid dimensions overflow"))?;
+        if self.cells.len() != expected || self.regimes.len() != expected {
+            return Err(E::custom(format!(
+                "expected {} cells for a {}x{} grid, found {} ({} regimes)",
+                expected,
+                self.width,
+                self.height,
+                self.cells.len(),
+                self.regimes.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Row-major cell regimes of `field`.
+fn regimes<F: TraceField>(field: &F) -> Vec<Regime> {
+    let (width, height) = field.dimensions();
+    positions(width, height)
+        .map(|(x, y)| field.regime(x, y))
+        .collect()
+}
+
+/// Row-major cell positions of a `width × height` grid.
+fn positions(width: usize, height: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+}
+
+impl Serialize for DensityGrid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (width, height) = self.dimensions();
+        GridState {
+            width,
+            height,
+            decay_rate: self.decay_rate(),
+            decay_model: self.decay_model(),
+            topology: self.topology(),
+            boundary: self.boundary(),
+            thresholds: self.regime_thresholds(),
+            cells: positions(width, height)
+                .map(|(x, y)| self.density(x, y))
+                .collect(),
+            regimes: regimes(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DensityGrid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = GridState::<u32>::deserialize(deserializer)?;
+        state.check()?;
+        let mut grid = DensityGrid::with_regimes(
+            state.width,
+            state.height,
+            state.decay_rate,
+            state.thresholds,
+        );
+        grid.set_decay_model(state.decay_model);
+        grid.set_topology(state.topology, state.boundary);
+        let cells = state.cells.into_iter().zip(state.regimes);
+        for ((x, y), (d, regime)) in positions(state.width, state.height).zip(cells) {
+            grid.restore(x, y, d, regime);
+        }
+        Ok(grid)
+    }
+}
+
+impl Serialize for IsotopeGrid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (width, height) = self.dimensions();
+        GridState {
+            width,
+            height,
+            decay_rate: self.decay_rate(),
+            decay_model: self.decay_model(),
+            topology: self.topology(),
+            boundary: self.boundary(),
+            thresholds: self.regime_thresholds(),
+            cells: positions(width, height)
+                .map(|(x, y)| {
+                    let (r, g, b) = self.rgb(x, y);
+                    [r, g, b]
+                })
+                .collect(),
+            regimes: regimes(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for IsotopeGrid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = GridState::<[u32; 3]>::deserialize(deserializer)?;
+        state.check()?;
+        let mut grid = IsotopeGrid::with_regimes(
+            state.width,
+            state.height,
+            state.decay_rate,
+            state.thresholds,
+        );
+        grid.set_decay_model(state.decay_model);
+        grid.set_topology(state.topology, state.boundary);
+        let cells = state.cells.into_iter().zip(state.regimes);
+        for ((x, y), ([r, g, b], regime)) in positions(state.width, state.height).zip(cells) {
+            r.checked_add(g)
+                .and_then(|rg| rg.checked_add(b))
+                .ok_or_else(|| D::Error::custom("cell density overflows"))?;
+            grid.restore(x, y, (r, g, b), regime);
+        }
+        Ok(grid)
+    }
+}
+
+/// Serialized form of a substrate.
+#[derive(Serialize, Deserialize)]
+struct SubstrateState<F> {
+    tick: u64,
+    threshold: u32,
+    field: F,
+}
+
+impl<P, F: TraceField + Serialize> Serialize for Substrate<P, F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SubstrateState {
+            tick: self.tick_count(),
+            threshold: self.space().threshold(),
+            field: self.space().field(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, P, F> Deserialize<'de> for Substrate<P, F>
+where
+    F: TraceField + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = SubstrateState::<F>::deserialize(deserializer)?;
+        check_threshold(state.threshold, state.field.regime_thresholds())
+            .map_err(D::Error::custom)?;
+        let mut substrate = Substrate::from_space(Space::from_field(state.field, state.threshold));
+        substrate.set_tick_count(state.tick);
+        Ok(substrate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::footprint::Footprint;
+    use crate::grid::Regime;
+    use crate::isotope::ServiceColor;
+    use crate::shape::Shape;
+
+    #[test]
+    fn test_plain_types_round_trip() {
+        let color = ServiceColor::from_name("AuthService");
+        let json = serde_json::to_string(&color).unwrap();
+        assert_eq!(serde_json::from_str::<ServiceColor>(&json).unwrap(), color);
+
+        let regime: Regime = serde_json::from_str("\"Liquid\"").unwrap();
+        assert_eq!(regime, Regime::Liquid);
+
+        let mut shape: Shape<String> = Shape::new(7, 1, 2, 100, 10, 1.0, 5);
+        shape.footprint = Footprint::Disk { radius: 2 };
+        shape.attach_payload("job".to_string());
+        let back: Shape<String> =
+            serde_json::from_str(&serde_json::to_string(&shape).unwrap()).unwrap();
+        assert_eq!(back.id, 7);
+        assert_eq!(back.footprint, Footprint::Disk { radius: 2 });
+        assert_eq!(back.payload().map(String::as_str), Some("job"));
+    }
+
+    #[test]
+    fn test_grids_round_trip() {
+        let grid = IsotopeGrid::with_regimes(3, 2, 4, RegimeThresholds::with_margin(900, 400, 30));
+        grid.contribute(2, 1, 500, ServiceColor::green());
+
+        let back: IsotopeGrid =
+            serde_json::from_str(&serde_json::to_string(&grid).unwrap()).unwrap();
+        assert_eq!(back.rgb(2, 1), (0, 500, 0));
+        assert_eq!(back.decay_rate(), 4);
+        assert_eq!(back.regime_thresholds(), grid.regime_thresholds());
+
+        let mut torus = DensityGrid::new(2, 2, 1, 1000, 500);
+        torus.set_topology(Topology::Torus, Boundary::Closed);
+        let json = serde_json::to_string(&torus).unwrap();
+        assert!(json.contains("\"topology\":\"torus\""));
+        let back: DensityGrid = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.topology(), Topology::Torus);
+
+        let bad = r#"{"width":2,"height":2,"decay_rate":0,
+            "thresholds":{"solid_enter":10,"solid_exit":10,"liquid_enter":5,"liquid_exit":5},
+            "cells":[1,2,3],"regimes":["Gas","Gas","Gas"]}"#;
+        let err = serde_json::from_str::<DensityGrid>(bad).err().unwrap();
+        assert!(err.to_string().contains("expected 4 cells"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_grid_state() {
+        let zero = r#"{"width":0,"height":2,"decay_rate":0,
+            "thresholds":{"solid_enter":10,"solid_exit":10,"liquid_enter":5,"liquid_exit":5},
+            "cells":[],"regimes":[]}"#;
+        assert!(serde_json::from_str::<DensityGrid>(zero).is_err());
+
+        let inverted = r#"{"width":1,"height":1,"decay_rate":0,
+            "thresholds":{"solid_enter":5,"solid_exit":5,"liquid_enter":10,"liquid_exit":10},
+            "cells":[[0,0,0]],"regimes":["Gas"]}"#;
+        assert!(serde_json::from_str::<IsotopeGrid>(inverted).is_err());
+
+        let decay = r#"{"width":1,"height":1,"decay_rate":2000,"decay_model":"exponential",
+            "thresholds":{"solid_enter":5000,"solid_exit":5000,"liquid_enter":10,"liquid_exit":10},
+            "cells":[0],"regimes":["Gas"]}"#;
+        assert!(serde_json::from_str::<DensityGrid>(decay).is_err());
+    }
+
+    #[test]
+    fn test_substrate_round_trip() {
+        let mut sub = Substrate::new(4, 4, 1, 100);
+        sub.spawn(1, 1, 20, 30).unwrap();
+        sub.run(3);
+
+        let json = serde_json::to_string(&sub).unwrap();
+        let back: Substrate = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.tick_count(), 3);
+        assert_eq!(back.space().threshold(), 100);
+        assert_eq!(back.density_map(), sub.density_map());
+        assert_eq!(back.shape_count(), 0);
+
+        let json = json.replace("\"threshold\":100", "\"threshold\":5000");
+        assert!(serde_json::from_str::<Substrate>(&json).is_err());
+    }
+
+    #[test]
+    fn test_keeps_cells_held_solid() {
+        let grid = DensityGrid::with_regimes(2, 1, 10, RegimeThresholds::with_margin(100, 50, 20));
+        grid.contribute(0, 0, 101);
+        grid.apply_decay();
+
+        let back: DensityGrid =
+            serde_json::from_str(&serde_json::to_string(&grid).unwrap()).unwrap();
+        assert_eq!((back.density(0, 0), back.regime(0, 0)), (91, Regime::Solid));
+    }
+}
@@ -3,8 +3,16 @@
 //! Shapes do NOT act. They only exist.
 //! They do not affect space; space affects them.
 
+use std::borrow::Cow;
+
+use crate::footprint::{Footprint, KernelCache};
+use crate::isotope::ServiceColor;
+
 /// A carrier that inhabitsa the topographic space.
 ///
+/// A shape does not carry information, it hosts it: the payload `P` is owned
+/// by the shape and is dropped when the shape dies.
+///
 /// # Formalization Mapping
 ///
 /// ```text
@@ -17,7 +25,8 @@
 /// - `λ` → lifetime (ticks remaining)
 /// - `σ` → sensitivity (how much space affects this shape)
 #[derive(Debug, Clone)]
-pub struct Shape {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shape<P = ()> {
     /// Unique identifier
     pub id: u64,
     /// Position in space (x, y)
@@ -30,11 +39,22 @@ pub struct Shape {
     pub sensitivity: f32,
     /// Contribution amount per tick
     pub contribution: u32,
-    /// Whether shape has payload (for A0 temporal observation)
-    pub has_payload: bool,
+    /// Region the contribution is spread over
+    pub footprint: Footprint,
+    /// Service color signature of the trace (isotope spaces only)
+    pub color: ServiceColor,
+    /// Hosted payload (for A0 temporal observation)
+    pub payload: Option<P>,
+    /// Group tag (for `Relation::Group`)
+    pub group: Option<u64>,
+    /// Whether shape is related to another shape (for A0 temporal observation)
+    pub related: bool,
+    /// Footprint kernel built at admission
+    #[cfg_attr(feature = "serde", serde(skip))]
+    kernel: KernelCache,
 }
 
-impl Shape {
+impl<P> Shape<P> {
     /// Create a new shape.
     pub fn new(
         id: u64,
@@ -52,20 +72,38 @@ impl Shape {
             lifetime,
             sensitivity,
             contribution,
-            has_payload: false,
+            footprint: Footprint::Point,
+            color: ServiceColor::neutral(),
+            payload: None,
+            group: None,
+            related: false,
+            kernel: KernelCache::default(),
         }
     }
 
     /// Attach payload to shape (activates temporal observation).
     ///
     /// From A0: `temporal_observation(s) ⟺ payload(s) ≠ ∅ ∧ ...`
-    pub fn attach_payload(&mut self) {
-        self.has_payload = true;
+    ///
+    /// Returns the previously hosted payload, if any.
+    pub fn attach_payload(&mut self, payload: P) -> Option<P> {
+        self.payload.replace(payload)
+    }
+
+    /// Detach payload, handing ownership back to the caller.
+    pub fn detach_payload(&mut self) -> Option<P> {
+        self.payload.take()
+    }
+
+    /// Check if shape hosts a payload.
+    #[inline]
+    pub fn has_payload(&self) -> bool {
+        self.payload.is_some()
     }
 
-    /// Detach payload.
-    pub fn detach_payload(&mut self) {
-        self.has_payload = false;
+    /// Get reference to the hosted payload.
+    pub fn payload(&self) -> Option<&P> {
+        self.payload.as_ref()
     }
 
     /// Spend one tick of lifetime.
@@ -88,13 +126,36 @@ impl Shape {
         self.budget > 0 && self.lifetime > 0
     }
 
+    /// Build the footprint kernel once, so checks and ticks reuse it.
+    pub(crate) fn build_kernel(&mut self) {
+        if self.footprint != Footprint::Point {
+            self.kernel = KernelCache::new(self.footprint, self.contribution);
+        }
+    }
+
+    /// Get footprint kernel, rebuilt only if the footprint has changed.
+    pub(crate) fn kernel(&self) -> Cow<'_, [(isize, isize, f32)]> {
+        match self.kernel.kernel(self.footprint) {
+            Some(kernel) => Cow::Borrowed(kernel),
+            None => Cow::Owned(self.footprint.kernel()),
+        }
+    }
+
+    /// Get contribution shares over the footprint.
+    pub(crate) fn shares(&self) -> Cow<'_, [(isize, isize, u32)]> {
+        match self.kernel.shares(self.footprint, self.contribution) {
+            Some(shares) => Cow::Borrowed(shares),
+            None => Cow::Owned(self.footprint.distribute(self.contribution)),
+        }
+    }
+
     /// Check if shape can produce trace.
     ///
     /// From A0: temporal observation requires payload + relationality.
-    /// For now, we only check payload.
+    /// `related` is evaluated by the substrate each tick (see `Relation`).
     #[inline]
     pub fn can_produce_trace(&self) -> bool {
-        self.is_alive() && self.has_payload
+        self.is_alive() && self.has_payload() && self.related
     }
 }
 
@@ -104,7 +165,7 @@ mod tests {
 
     #[test]
     fn test_lifetime_decay() {
-        let mut shape = Shape::new(1, 5, 5, 100, 10, 1.0, 5);
+        let mut shape: Shape = Shape::new(1, 5, 5, 100, 10, 1.0, 5);
         assert!(shape.is_alive());
 
         // Tick 10 times
@@ -123,15 +184,32 @@ mod tests {
         // No payload = no trace
         assert!(!shape.can_produce_trace());
 
-        // Attach payload
-        shape.attach_payload();
+        // Attach payload - still no trace without a relation (A0)
+        shape.attach_payload(());
+        assert!(!shape.can_produce_trace());
+
+        // Payload + relationality = trace
+        shape.related = true;
         assert!(shape.can_produce_trace());
     }
 
+    #[test]
+    fn test_payload_ownership() {
+        let mut shape = Shape::new(1, 5, 5, 100, 10, 1.0, 5);
+
+        assert_eq!(shape.attach_payload("job-a"), None);
+        assert_eq!(shape.attach_payload("job-b"), Some("job-a"));
+        assert_eq!(shape.payload(), Some(&"job-b"));
+
+        // Detaching hands the payload back and stops trace production
+        assert_eq!(shape.detach_payload(), Some("job-b"));
+        assert!(!shape.can_produce_trace());
+    }
+
     #[test]
     fn test_death_inevitability() {
         // A5: ∀s: ∃t*: λ(s, t*) = 0 ⟹ ¬inhabits(s, space)
-        let mut shape = Shape::new(1, 0, 0, 100, 5, 1.0, 1);
+        let mut shape: Shape = Shape::new(1, 0, 0, 100, 5, 1.0, 1);
 
         let mut ticks = 0;
         while shape.is_alive() {
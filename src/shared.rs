@@ -0,0 +1,608 @@
+//! Shared grid - one field across processes (feature `shm`)
+//!
+//! A [`SharedGrid`] keeps its cells in a memory-mapped file instead of a
+//! process-private `Vec`, so worker processes on the same host contribute
+//! to and read from one field. On Linux, a path under `/dev/shm` gives a
+//! POSIX shared memory segment.
+//!
+//! Contributions and reads are atomic per cell, exactly as in
+//! [`DensityGrid`](crate::DensityGrid). Decay and diffusion are global
+//! operations and must happen once per tick, not once per process: only
+//! the handle holding the decayer role applies them; on every other handle
+//! they are no-ops. The role belongs to one handle, not one process, and
+//! is recorded in the segment: a handle that loses it stops decaying, and
+//! a role left behind by a crashed process can be claimed again.
+//!
+//! # Layout
+//!
+//! Native-endian (the segment never leaves the host):
+//!
+//! ```text
+//! "TESG" | version: u16 | layout: u8 | flags: u8
+//! width: u32 | height: u32 | decay_rate: u32
+//! solid_enter | solid_exit | liquid_enter | liquid_exit: u32
+//! 0u32 | decayer: u64 (pid << 32 | handle, 0 = none) | ticks: u64
+//! cells: width × height × u32 | regimes: width × height × u8
+//! ```
+//!
+//! `flags` are the field flags of a snapshot (see [`crate::Snapshot`]).
+
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+
+use memmap2::MmapMut;
+
+use crate::error::TesError;
+use crate::field::TraceField;
+use crate::grid::{
+    cell_count, check_grid, decay_cells, diffuse_cells, load_regime, DecayModel, Regime,
+    RegimeThresholds,
+};
+use crate::snapshot::{decode_flags, encode_flags};
+use crate::topology::{Boundary, Lattice, Topology};
+
+/// Current shared segment layout version.
+pub const SHARED_VERSION: u16 = 1;
+
+const MAGIC: [u8; 4] = *b"TESG";
+const LAYOUT_DENSITY: u8 = 0;
+
+const OFF_RESERVED: usize = 36;
+const OFF_DECAYER: usize = 40;
+const OFF_TICKS: usize = 48;
+const HEADER_LEN: usize = 56;
+
+/// Serial of the next handle opened in this process (never 0).
+static NEXT_HANDLE: AtomicU32 = AtomicU32::new(1);
+
+/// A density field living in a memory-mapped file.
+pub struct SharedGrid {
+    /// Keeps the mapping alive; not accessed as a byte slice once opened,
+    /// since other processes write to it concurrently
+    _map: MmapMut,
+    base: NonNull<u8>,
+    width: usize,
+    height: usize,
+    decay_rate: u32,
+    decay_model: DecayModel,
+    topology: Topology,
+    boundary: Boundary,
+    thresholds: RegimeThresholds,
+    /// Decayer token of this handle: pid << 32 | handle serial
+    token: u64,
+}
+
+// SAFETY: all access through `base` is atomic, and the mapping outlives it.
+unsafe impl Send for SharedGrid {}
+unsafe impl Sync for SharedGrid {}
+
+impl SharedGrid {
+    /// Create a segment at `path` holding an empty grid.
+    ///
+    /// Fails if `path` already exists: resizing a file other processes
+    /// still map would fault them. Remove a stale segment first.
+    ///
+    /// Parameters are validated like
+    /// [`DensityGrid::try_with_regimes`](crate::DensityGrid::try_with_regimes).
+    pub fn create(
+        path: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        Self::create_with(
+            path,
+            width,
+            height,
+            decay_rate,
+            thresholds,
+            (
+                DecayModel::default(),
+                Topology::default(),
+                Boundary::default(),
+            ),
+        )
+    }
+
+    /// Create a segment with explicit decay model, topology and boundary.
+    pub fn create_with(
+        path: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+        (decay_model, topology, boundary): (DecayModel, Topology, Boundary),
+    ) -> Result<Self, TesError> {
+        check_grid(width, height, decay_model, decay_rate, thresholds)?;
+        let len = segment_len(width, height).ok_or(TesError::TooLarge { width, height })?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        file.set_len(len as u64)?;
+        // SAFETY: the file was just sized; concurrent openers see either a
+        // zeroed header (rejected) or the complete one written below.
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+
+        let mut header = Vec::with_capacity(OFF_RESERVED);
+        header.extend_from_slice(&SHARED_VERSION.to_ne_bytes());
+        header.push(LAYOUT_DENSITY);
+        header.push(encode_flags(decay_model, topology, boundary));
+        let t = thresholds;
+        for v in [
+            width as u32,
+            height as u32,
+            decay_rate,
+            t.solid_enter,
+            t.solid_exit,
+            t.liquid_enter,
+            t.liquid_exit,
+        ] {
+            header.extend_from_slice(&v.to_ne_bytes());
+        }
+        map[4..OFF_RESERVED].copy_from_slice(&header);
+        // Magic last: the segment is valid once it is visible
+        map[..4].copy_from_slice(&MAGIC);
+        map.flush()?;
+
+        Self::from_map(map)
+    }
+
+    /// Open an existing segment.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TesError> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Self::open_file(&file)
+    }
+
+    /// Open an existing segment from an open file.
+    pub fn open_file(file: &File) -> Result<Self, TesError> {
+        // SAFETY: the mapping is only accessed atomically (see `SharedGrid`)
+        let map = unsafe { MmapMut::map_mut(file)? };
+        Self::from_map(map)
+    }
+
+    fn from_map(mut map: MmapMut) -> Result<Self, TesError> {
+        if map.len() < HEADER_LEN {
+            return Err(TesError::InvalidSegment("segment shorter than its header"));
+        }
+        if map[..4] != MAGIC {
+            return Err(TesError::InvalidSegment("not a TES grid segment"));
+        }
+        let u16_at = |off: usize| u16::from_ne_bytes([map[off], map[off + 1]]);
+        let u32_at = |off: usize| u32::from_ne_bytes(map[off..off + 4].try_into().unwrap());
+        if u16_at(4) != SHARED_VERSION {
+            return Err(TesError::InvalidSegment("unsupported segment version"));
+        }
+        if map[6] != LAYOUT_DENSITY {
+            return Err(TesError::InvalidSegment("unsupported cell layout"));
+        }
+        let (decay_model, topology, boundary) =
+            decode_flags(map[7]).ok_or(TesError::InvalidSegment("unknown field flags"))?;
+
+        let width = u32_at(8) as usize;
+        let height = u32_at(12) as usize;
+        let decay_rate = u32_at(16);
+        let thresholds = RegimeThresholds {
+            solid_enter: u32_at(20),
+            solid_exit: u32_at(24),
+            liquid_enter: u32_at(28),
+            liquid_exit: u32_at(32),
+        };
+        check_grid(width, height, decay_model, decay_rate, thresholds).map_err(invalid)?;
+        if u32_at(OFF_RESERVED) != 0 {
+            return Err(TesError::InvalidSegment("reserved header bytes set"));
+        }
+        if segment_len(width, height) != Some(map.len()) {
+            return Err(TesError::InvalidSegment(
+                "segment size does not match header",
+            ));
+        }
+
+        let base = NonNull::new(map.as_mut_ptr()).expect("mapping is non-null");
+        Ok(Self {
+            _map: map,
+            base,
+            width,
+            height,
+            decay_rate,
+            decay_model,
+            topology,
+            boundary,
+            thresholds,
+            token: (std::process::id() as u64) << 32
+                | NEXT_HANDLE.fetch_add(1, Ordering::Relaxed).max(1) as u64,
+        })
+    }
+
+    /// Take the decayer role if it is free or its process has exited.
+    ///
+    /// Returns whether this handle is now the decayer.
+    pub fn claim_decayer(&self) -> bool {
+        let slot = self.decayer_slot();
+        let mut holder = slot.load(Ordering::Acquire);
+        loop {
+            if holder == self.token {
+                return true;
+            }
+            if holder != 0 && process_alive((holder >> 32) as u32) {
+                return false;
+            }
+            match slot.compare_exchange(holder, self.token, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return true,
+                Err(current) => holder = current,
+            }
+        }
+    }
+
+    /// Take the decayer role unconditionally; the previous holder stops
+    /// decaying, even in the same process.
+    pub fn take_over_decayer(&self) {
+        self.decayer_slot().store(self.token, Ordering::Release);
+    }
+
+    /// Give up the decayer role (also done on drop).
+    pub fn release_decayer(&self) {
+        let _ = self.decayer_slot().compare_exchange(
+            self.token,
+            0,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+    }
+
+    /// Check if this handle holds the decayer role (read from the segment).
+    pub fn is_decayer(&self) -> bool {
+        self.decayer_slot().load(Ordering::Acquire) == self.token
+    }
+
+    /// Process id of the current decayer (None if the role is free).
+    pub fn decayer(&self) -> Option<u32> {
+        match self.decayer_slot().load(Ordering::Acquire) {
+            0 => None,
+            holder => Some((holder >> 32) as u32),
+        }
+    }
+
+    /// Decay ticks applied to the segment so far (by any process).
+    pub fn ticks(&self) -> u64 {
+        self.ticks_counter().load(Ordering::Acquire)
+    }
+
+    /// Get decay per tick (fixed-point, 1000 = 1.0).
+    pub fn decay_rate(&self) -> u32 {
+        self.decay_rate
+    }
+
+    /// Get how decay is applied.
+    pub fn decay_model(&self) -> DecayModel {
+        self.decay_model
+    }
+
+    /// Get edge behavior.
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    /// Get the regime boundaries.
+    pub fn regime_thresholds(&self) -> RegimeThresholds {
+        self.thresholds
+    }
+
+    fn cells(&self) -> &[AtomicU32] {
+        // SAFETY: `from_map` checked the mapping holds width × height cells
+        // after the 8-aligned header; mappings are page-aligned.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.base.as_ptr().add(HEADER_LEN) as *const AtomicU32,
+                self.width * self.height,
+            )
+        }
+    }
+
+    fn regimes(&self) -> &[AtomicU8] {
+        let n = self.width * self.height;
+        // SAFETY: as for `cells`; the regime bytes follow the cells.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.base.as_ptr().add(HEADER_LEN + n * 4) as *const AtomicU8,
+                n,
+            )
+        }
+    }
+
+    fn decayer_slot(&self) -> &AtomicU64 {
+        // SAFETY: in-header, 8-aligned
+        unsafe { &*(self.base.as_ptr().add(OFF_DECAYER) as *const AtomicU64) }
+    }
+
+    fn ticks_counter(&self) -> &AtomicU64 {
+        // SAFETY: in-header, 8-aligned
+        unsafe { &*(self.base.as_ptr().add(OFF_TICKS) as *const AtomicU64) }
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+}
+
+impl Drop for SharedGrid {
+    fn drop(&mut self) {
+        self.release_decayer();
+    }
+}
+
+/// Whether process `pid` still exists.
+///
+/// A reused pid reads as alive; the role then needs
+/// [`SharedGrid::take_over_decayer`].
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks that the process exists
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+/// Liveness is unknown off Unix; a held role is never reclaimed.
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Header values a validated constructor would reject.
+fn invalid(e: TesError) -> TesError {
+    TesError::InvalidSegment(match e {
+        TesError::ZeroDimension => "zero dimensions",
+        TesError::TooLarge { .. } => "dimensions too large",
+        TesError::InvertedThresholds(_) => "inverted regime thresholds",
+        _ => "decay rate out of range",
+    })
+}
+
+/// Segment size in bytes for a `width × height` grid.
+fn segment_len(width: usize, height: usize) -> Option<usize> {
+    let cells = cell_count(width, height)?;
+    HEADER_LEN.checked_add(cells.checked_mul(5)?)
+}
+
+impl TraceField for SharedGrid {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn contribute(&self, x: usize, y: usize, amount: u32) {
+        if let Some(i) = self.index(x, y) {
+            self.cells()[i].fetch_add(amount, Ordering::Relaxed);
+            self.thresholds.settle(&self.regimes()[i], &self.cells()[i]);
+        }
+    }
+
+    fn try_contribute(&self, x: usize, y: usize, amount: u32, threshold: u32) -> bool {
+        let Some(i) = self.index(x, y) else {
+            return false;
+        };
+        if load_regime(&self.regimes()[i]) == Regime::Solid {
+            return false;
+        }
+        let admitted = self.cells()[i]
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |d| {
+                (d < threshold).then(|| d + amount)
+            })
+            .is_ok();
+        if admitted {
+            self.thresholds.settle(&self.regimes()[i], &self.cells()[i]);
+        }
+        admitted
+    }
+
+    fn density(&self, x: usize, y: usize) -> u32 {
+        self.index(x, y)
+            .map(|i| self.cells()[i].load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+
+    fn regime(&self, x: usize, y: usize) -> Regime {
+        self.index(x, y)
+            .map_or(Regime::Gas, |i| load_regime(&self.regimes()[i]))
+    }
+
+    fn regime_thresholds(&self) -> RegimeThresholds {
+        self.thresholds
+    }
+
+    fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Decay the segment; no-op unless this handle is the decayer.
+    fn apply_decay(&self) {
+        if self.is_decayer() {
+            decay_cells(self.cells(), self.decay_model, self.decay_rate);
+            self.thresholds.settle_all(self.regimes(), self.cells());
+            self.ticks_counter().fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Diffuse the segment; no-op unless this handle is the decayer.
+    fn diffuse(&self) {
+        if self.is_decayer() {
+            let lattice = Lattice {
+                width: self.width,
+                height: self.height,
+                topology: self.topology,
+                boundary: self.boundary,
+            };
+            diffuse_cells(self.cells(), lattice);
+            self.thresholds.settle_all(self.regimes(), self.cells());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::space::Space;
+    use std::path::PathBuf;
+
+    /// Segment path unique to one test, removed on drop.
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let file = format!("tes-shared-{}-{}", std::process::id(), name);
+            Self(std::env::temp_dir().join(file))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_handles_share_one_field() {
+        let path = TempPath::new("share");
+        let a = SharedGrid::create(&path.0, 8, 4, 10, RegimeThresholds::new(1000, 500)).unwrap();
+        let b = SharedGrid::open(&path.0).unwrap();
+
+        assert_eq!(b.dimensions(), (8, 4));
+        a.contribute(3, 2, 600);
+        assert_eq!(b.density(3, 2), 600);
+        assert_eq!(b.regime(3, 2), Regime::Liquid);
+        assert!(b.try_contribute(3, 2, 500, 1000));
+        assert!(!a.try_contribute(3, 2, 1, 1000));
+        assert_eq!(a.density(3, 2), 1100);
+    }
+
+    #[test]
+    fn test_single_decayer() {
+        let path = TempPath::new("decayer");
+        let a = SharedGrid::create(&path.0, 2, 2, 10, RegimeThresholds::new(1000, 500)).unwrap();
+        let b = SharedGrid::open(&path.0).unwrap();
+        a.contribute(0, 0, 100);
+
+        // No decayer yet: ticks do nothing
+        b.apply_decay();
+        assert_eq!(a.density(0, 0), 100);
+
+        assert!(a.claim_decayer());
+        assert!(!b.claim_decayer());
+        assert_eq!(b.decayer(), Some(std::process::id()));
+
+        let space = Space::from_field(b, 1000);
+        space.tick();
+        assert_eq!(a.density(0, 0), 100);
+        a.apply_decay();
+        assert_eq!(space.density(0, 0), 90);
+        assert_eq!(space.field().ticks(), 1);
+
+        // Role passes on once released
+        drop(a);
+        assert_eq!(space.field().decayer(), None);
+        assert!(space.field().claim_decayer());
+        space.tick();
+        assert_eq!(space.density(0, 0), 80);
+    }
+
+    #[test]
+    fn test_decayer_role_follows_the_segment() {
+        let path = TempPath::new("takeover");
+        let a = SharedGrid::create(&path.0, 1, 1, 10, RegimeThresholds::new(1000, 500)).unwrap();
+        let b = SharedGrid::open(&path.0).unwrap();
+        a.contribute(0, 0, 100);
+
+        // Same process, two handles: a take-over stops the old holder
+        assert!(a.claim_decayer());
+        b.take_over_decayer();
+        assert!(!a.is_decayer());
+        a.apply_decay();
+        b.apply_decay();
+        assert_eq!(a.density(0, 0), 90);
+
+        // A crashed holder's role can be claimed again
+        if cfg!(unix) {
+            let mut child = std::process::Command::new("true").spawn().unwrap();
+            let dead = child.id() as u64;
+            child.wait().unwrap();
+            a.decayer_slot().store(dead << 32 | 1, Ordering::Release);
+            assert_eq!(b.decayer(), Some(dead as u32));
+            assert!(b.claim_decayer());
+            assert!(!a.claim_decayer());
+        }
+    }
+
+    #[test]
+    fn test_header_round_trip_and_validation() {
+        let path = TempPath::new("header");
+        let thresholds = RegimeThresholds::with_margin(900, 400, 50);
+        let lattice = (DecayModel::Exponential, Topology::Torus, Boundary::Open);
+        drop(SharedGrid::create_with(&path.0, 5, 3, 7, thresholds, lattice).unwrap());
+
+        let grid = SharedGrid::open(&path.0).unwrap();
+        assert_eq!(grid.decay_rate(), 7);
+        assert_eq!(grid.regime_thresholds(), thresholds);
+        assert_eq!(grid.decay_model(), DecayModel::Exponential);
+        assert_eq!(grid.topology(), Topology::Torus);
+        assert_eq!(grid.boundary(), Boundary::Open);
+        drop(grid);
+
+        // Truncated segment
+        let file = OpenOptions::new().write(true).open(&path.0).unwrap();
+        file.set_len(HEADER_LEN as u64 + 4).unwrap();
+        assert!(matches!(
+            SharedGrid::open(&path.0),
+            Err(TesError::InvalidSegment(_))
+        ));
+
+        // Not a segment
+        std::fs::write(&path.0, [0u8; 64]).unwrap();
+        assert!(matches!(
+            SharedGrid::open(&path.0),
+            Err(TesError::InvalidSegment("not a TES grid segment"))
+        ));
+
+        assert!(matches!(
+            SharedGrid::create(&path.0, 0, 3, 1, thresholds),
+            Err(TesError::ZeroDimension)
+        ));
+    }
+
+    #[test]
+    fn test_rejects_invalid_header_and_existing_path() {
+        let path = TempPath::new("invalid");
+        let thresholds = RegimeThresholds::new(1000, 500);
+        drop(SharedGrid::create(&path.0, 2, 2, 10, thresholds).unwrap());
+        assert!(matches!(
+            SharedGrid::create(&path.0, 2, 2, 10, thresholds),
+            Err(TesError::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists
+        ));
+
+        // Liquid boundary above the Solid one
+        let mut bytes = std::fs::read(&path.0).unwrap();
+        bytes[28..32].copy_from_slice(&2000u32.to_ne_bytes());
+        std::fs::write(&path.0, &bytes).unwrap();
+        assert!(matches!(
+            SharedGrid::open(&path.0),
+            Err(TesError::InvalidSegment("inverted regime thresholds"))
+        ));
+
+        // Linear decay above the Solid boundary
+        bytes[28..32].copy_from_slice(&thresholds.liquid_enter.to_ne_bytes());
+        bytes[16..20].copy_from_slice(&5000u32.to_ne_bytes());
+        std::fs::write(&path.0, &bytes).unwrap();
+        assert!(matches!(
+            SharedGrid::open(&path.0),
+            Err(TesError::InvalidSegment("decay rate out of range"))
+        ));
+    }
+}
@@ -0,0 +1,310 @@
+//! Slit-scan - the temporal flux timeline
+//!
+//! A [`SlitScan`] samples a 1-cell cut through the space (a row, a column
+//! or a line) every time it records, keeping the last `depth` cuts in a
+//! ring buffer. Laid side by side - oldest left, newest right - the cuts
+//! show how congestion along the slit evolves (docs/gui.md, 3.2).
+//!
+//! Only densities are recorded, never shapes (Source Amnesia).
+
+use crate::error::TesError;
+use crate::field::TraceField;
+
+/// Cut through the space sampled by a [`SlitScan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slit {
+    /// Row `y`, left to right
+    Row(usize),
+    /// Column `x`, top to bottom
+    Column(usize),
+    /// Straight line between two cells, both included
+    Line {
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+}
+
+impl Slit {
+    /// Cells of the cut on a `width` x `height` field, in order.
+    ///
+    /// Cells outside the field are skipped.
+    pub fn positions(&self, width: usize, height: usize) -> Vec<(usize, usize)> {
+        match *self {
+            Slit::Row(y) if y < height => (0..width).map(|x| (x, y)).collect(),
+            Slit::Column(x) if x < width => (0..height).map(|y| (x, y)).collect(),
+            Slit::Row(_) | Slit::Column(_) => Vec::new(),
+            Slit::Line { from, to } => line(from, to, width, height).collect(),
+        }
+    }
+}
+
+/// Bresenham line from `from` to `to`, both included, clipped to a
+/// `width` x `height` field.
+///
+/// Each step along the major axis is placed directly (ties rounded away
+/// from the start, as Bresenham does), so only steps inside the field are
+/// visited however far away the endpoints lie.
+fn line(
+    from: (usize, usize),
+    to: (usize, usize),
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    let (x0, y0) = (from.0 as i128, from.1 as i128);
+    let (dx, dy) = (to.0 as i128 - x0, to.1 as i128 - y0);
+    let x_major = dx.abs() >= dy.abs();
+    let steps = dx.abs().max(dy.abs());
+    let (start, dir, limit) = if x_major {
+        (x0, dx.signum(), width as i128)
+    } else {
+        (y0, dy.signum(), height as i128)
+    };
+    // Steps whose major coordinate lies in 0..limit
+    let (first, last) = match dir {
+        1 => (-start, limit - 1 - start),
+        -1 => (start - (limit - 1), start),
+        _ => (0, 0),
+    };
+    // Minor offset after `i` steps, |i * d| / steps rounded half up
+    let offset = move |i: i128, d: i128| {
+        if steps == 0 {
+            return 0;
+        }
+        let (n, steps) = (i as u128 * d.unsigned_abs(), steps as u128);
+        let q = (n / steps + u128::from(2 * (n % steps) >= steps)) as i128;
+        q * d.signum()
+    };
+    (first.max(0)..=last.min(steps)).filter_map(move |i| {
+        let (x, y) = if x_major {
+            (x0 + dir * i, y0 + offset(i, dy))
+        } else {
+            (x0 + offset(i, dx), y0 + dir * i)
+        };
+        let cell = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+        (cell.0 < width && cell.1 < height).then_some(cell)
+    })
+}
+
+/// One cell of a recorded cut.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Sample {
+    density: u32,
+    channels: Option<(u32, u32, u32)>,
+}
+
+/// Ring buffer of the last `depth` cuts through a field.
+#[derive(Debug, Clone)]
+pub struct SlitScan {
+    slit: Slit,
+    positions: Vec<(usize, usize)>,
+    depth: usize,
+    /// `depth` cuts of `positions.len()` samples each
+    samples: Vec<Sample>,
+    /// Cut written by the next [`SlitScan::record`]
+    head: usize,
+    recorded: usize,
+}
+
+impl SlitScan {
+    /// Record `slit` through `field` (which must keep its dimensions),
+    /// keeping the last `depth` cuts.
+    ///
+    /// # Panics
+    ///
+    /// Panics where [`SlitScan::try_new`] fails.
+    pub fn new<F: TraceField + ?Sized>(field: &F, slit: Slit, depth: usize) -> Self {
+        Self::try_new(field, slit, depth).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible [`SlitScan::new`].
+    ///
+    /// Fails if `slit` misses the field entirely, or if `depth` cuts of it
+    /// are too large to allocate.
+    pub fn try_new<F: TraceField + ?Sized>(
+        field: &F,
+        slit: Slit,
+        depth: usize,
+    ) -> Result<Self, TesError> {
+        let (width, height) = field.dimensions();
+        let positions = slit.positions(width, height);
+        if positions.is_empty() {
+            return Err(TesError::EmptySlit);
+        }
+        let depth = depth.max(1);
+        let len = depth
+            .checked_mul(positions.len())
+            .filter(|&n| n <= isize::MAX as usize / std::mem::size_of::<Sample>())
+            .ok_or(TesError::TooLarge {
+                width: depth,
+                height: positions.len(),
+            })?;
+        Ok(Self {
+            slit,
+            samples: vec![Sample::default(); len],
+            positions,
+            depth,
+            head: 0,
+            recorded: 0,
+        })
+    }
+
+    /// Sample the cut through `field` now (e.g. once per tick), replacing
+    /// the oldest cut once the buffer is full.
+    pub fn record<F: TraceField + ?Sized>(&mut self, field: &F) {
+        let len = self.positions.len();
+        let cut = &mut self.samples[self.head * len..(self.head + 1) * len];
+        for (sample, &(x, y)) in cut.iter_mut().zip(&self.positions) {
+            *sample = Sample {
+                density: field.density(x, y),
+                channels: field.channels(x, y),
+            };
+        }
+        self.head = (self.head + 1) % self.depth;
+        self.recorded = (self.recorded + 1).min(self.depth);
+    }
+
+    /// Get the slit.
+    pub fn slit(&self) -> Slit {
+        self.slit
+    }
+
+    /// Get the sampled cells, in cut order.
+    pub fn positions(&self) -> &[(usize, usize)] {
+        &self.positions
+    }
+
+    /// Get the number of cuts kept.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get the number of cuts recorded so far (at most `depth`).
+    pub fn len(&self) -> usize {
+        self.recorded
+    }
+
+    /// Check if nothing was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.recorded == 0
+    }
+
+    /// Forget all cuts.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.recorded = 0;
+    }
+
+    fn sample(&self, age: usize, i: usize) -> Option<&Sample> {
+        if age >= self.recorded || i >= self.positions.len() {
+            return None;
+        }
+        let cut = (self.head + self.depth - 1 - age) % self.depth;
+        self.samples.get(cut * self.positions.len() + i)
+    }
+
+    /// Get the density at cell `i` of the cut recorded `age` records ago
+    /// (0 = newest).
+    pub fn density(&self, age: usize, i: usize) -> Option<u32> {
+        self.sample(age, i).map(|s| s.density)
+    }
+
+    /// Get the channels at cell `i` of the cut recorded `age` records ago.
+    ///
+    /// None if out of range or the field has no channels.
+    pub fn channels(&self, age: usize, i: usize) -> Option<(u32, u32, u32)> {
+        self.sample(age, i).and_then(|s| s.channels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::DensityGrid;
+    use crate::isotope::{IsotopeGrid, ServiceColor};
+
+    #[test]
+    fn test_slit_positions() {
+        assert_eq!(Slit::Row(1).positions(3, 2), [(0, 1), (1, 1), (2, 1)]);
+        assert_eq!(Slit::Column(2).positions(3, 2), [(2, 0), (2, 1)]);
+        assert!(Slit::Row(5).positions(3, 2).is_empty());
+
+        let diagonal = Slit::Line {
+            from: (3, 3),
+            to: (0, 0),
+        };
+        assert_eq!(diagonal.positions(8, 8), [(3, 3), (2, 2), (1, 1), (0, 0)]);
+        let shallow = Slit::Line {
+            from: (0, 0),
+            to: (4, 1),
+        };
+        assert_eq!(shallow.positions(8, 8).len(), 5);
+        // Clipped to the field
+        assert_eq!(shallow.positions(3, 8), [(0, 0), (1, 0), (2, 1)]);
+
+        // Far endpoints are clipped before walking the line
+        let far = Slit::Line {
+            from: (0, 1),
+            to: (usize::MAX, 1),
+        };
+        assert_eq!(far.positions(3, 2), [(0, 1), (1, 1), (2, 1)]);
+        let steep = Slit::Line {
+            from: (usize::MAX, usize::MAX),
+            to: (0, 0),
+        };
+        assert_eq!(steep.positions(2, 2), [(1, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn test_ring_keeps_last_cuts() {
+        let grid = DensityGrid::new(3, 3, 0, 1000, 500);
+        let mut scan = SlitScan::new(&grid, Slit::Column(1), 2);
+        assert!(scan.is_empty());
+        assert_eq!(scan.density(0, 0), None);
+
+        for tick in 1..=3 {
+            grid.contribute(1, 2, 10);
+            scan.record(&grid);
+            assert_eq!(scan.density(0, 2), Some(10 * tick));
+        }
+        assert_eq!(scan.len(), 2);
+        assert_eq!(scan.density(1, 2), Some(20));
+        assert_eq!(scan.density(2, 2), None);
+        assert_eq!(scan.density(0, 3), None);
+        assert_eq!(scan.channels(0, 2), None);
+
+        scan.clear();
+        assert!(scan.is_empty());
+    }
+
+    #[test]
+    fn test_records_channels() {
+        let grid = IsotopeGrid::new(4, 1, 0, 1000, 500);
+        let mut scan = SlitScan::new(&grid, Slit::Row(0), 8);
+        grid.contribute(3, 0, 90, ServiceColor::green());
+        scan.record(&grid);
+
+        assert_eq!(scan.positions().len(), 4);
+        assert_eq!(scan.channels(0, 3), Some((0, 90, 0)));
+        assert_eq!(scan.density(0, 3), Some(90));
+    }
+
+    #[test]
+    fn test_rejects_invalid_scans() {
+        let grid = DensityGrid::new(3, 3, 0, 1000, 500);
+        assert!(matches!(
+            SlitScan::try_new(&grid, Slit::Row(3), 4),
+            Err(TesError::EmptySlit)
+        ));
+        assert!(matches!(
+            SlitScan::try_new(&grid, Slit::Row(0), usize::MAX),
+            Err(TesError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "slit lies outside the field")]
+    fn test_new_panics_on_empty_slit() {
+        let grid = DensityGrid::new(3, 3, 0, 1000, 500);
+        SlitScan::new(&grid, Slit::Column(5), 4);
+    }
+}
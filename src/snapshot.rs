@@ -0,0 +1,625 @@
+//! Snapshot - persistence of the field across restarts
+//!
+//! A restart that wipes all trace makes every service look idle at once
+//! (thundering herd). Snapshots store the terrain, not its history:
+//! dimensions, decay parameters and cell values. No shape, payload or
+//! contribution is recorded (Source Amnesia); a restored substrate has
+//! the same topography and no inhabitants.
+//!
+//! # Format
+//!
+//! Little-endian, versioned. A field snapshot is
+//!
+//! ```text
+//! "TESF" | version: u16 | layout: u8 | flags: u8
+//! width: u32 | height: u32 | decay_rate: u32
+//! solid_enter | solid_exit | liquid_enter | liquid_exit: u32
+//! cells: width × height × (1 | 3) u32   (density | r, g, b)
+//! regimes: width × height u8             (0 Solid, 1 Liquid, 2 Gas)
+//! ```
+//!
+//! `flags` bit 0 marks exponential decay, bit 1 an open boundary and
+//! bit 2 a torus; other bits must be zero.
+//!
+//! A substrate snapshot is a small header followed by its field snapshot:
+//!
+//! ```text
+//! "TESS" | version: u16 | 0u16 | tick: u64 | threshold: u32 | field
+//! ```
+//!
+//! Regimes are stored so a cell held Solid by hysteresis stays held
+//! across a restart; each still settles against its restored density.
+//! The substrate threshold must be positive and not exceed the field's
+//! Solid boundary.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::error::TesError;
+use crate::field::TraceField;
+use crate::grid::{check_grid, check_threshold, DecayModel, DensityGrid, Regime, RegimeThresholds};
+use crate::isotope::IsotopeGrid;
+use crate::space::Space;
+use crate::substrate::Substrate;
+use crate::topology::{Boundary, Topology};
+
+/// Current snapshot format version.
+pub const SNAPSHOT_VERSION: u16 = 2;
+
+const FIELD_MAGIC: [u8; 4] = *b"TESF";
+const SUBSTRATE_MAGIC: [u8; 4] = *b"TESS";
+
+/// Cell layout tag of a field snapshot.
+const LAYOUT_DENSITY: u8 = 0;
+const LAYOUT_ISOTOPE: u8 = 1;
+
+/// Field flag bits.
+const FLAG_EXPONENTIAL: u8 = 1 << 0;
+const FLAG_OPEN: u8 = 1 << 1;
+const FLAG_TORUS: u8 = 1 << 2;
+
+/// Error while writing or reading a snapshot.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Underlying reader/writer failed
+    Io(io::Error),
+    /// Input is not a TES snapshot (or not of the expected kind)
+    BadMagic,
+    /// Snapshot was written by an incompatible format version
+    UnsupportedVersion(u16),
+    /// Snapshot holds a different cell layout than requested
+    LayoutMismatch { expected: u8, found: u8 },
+    /// Input ended before the snapshot was complete
+    Truncated,
+    /// Header or cell values are inconsistent
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "snapshot I/O error: {}", e),
+            Self::BadMagic => write!(f, "not a TES snapshot"),
+            Self::UnsupportedVersion(v) => write!(
+                f,
+                "unsupported snapshot version {} (expected {})",
+                v, SNAPSHOT_VERSION
+            ),
+            Self::LayoutMismatch { expected, found } => write!(
+                f,
+                "snapshot cell layout {} does not match expected layout {}",
+                found, expected
+            ),
+            Self::Truncated => write!(f, "snapshot is truncated"),
+            Self::Corrupt(what) => write!(f, "corrupt snapshot: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => Self::Truncated,
+            _ => Self::Io(e),
+        }
+    }
+}
+
+/// Something whose state can be written to and restored from a snapshot.
+pub trait Snapshot: Sized {
+    /// Write a snapshot to `out`.
+    fn write_snapshot<W: Write>(&self, out: &mut W) -> Result<(), SnapshotError>;
+
+    /// Restore from a snapshot read from `input`.
+    fn read_snapshot<R: Read>(input: &mut R) -> Result<Self, SnapshotError>;
+
+    /// Snapshot into a byte buffer.
+    fn to_snapshot(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_snapshot(&mut out)
+            .expect("writing to a Vec cannot fail");
+        out
+    }
+
+    /// Restore from a byte buffer.
+    fn from_snapshot(mut bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Self::read_snapshot(&mut bytes)
+    }
+}
+
+/// Field header shared by all layouts.
+struct FieldHeader {
+    layout: u8,
+    width: usize,
+    height: usize,
+    decay_rate: u32,
+    decay_model: DecayModel,
+    topology: Topology,
+    boundary: Boundary,
+    thresholds: RegimeThresholds,
+}
+
+/// Pack lattice settings into field flags.
+pub(crate) fn encode_flags(model: DecayModel, topology: Topology, boundary: Boundary) -> u8 {
+    let mut flags = 0;
+    if model == DecayModel::Exponential {
+        flags |= FLAG_EXPONENTIAL;
+    }
+    if boundary == Boundary::Open {
+        flags |= FLAG_OPEN;
+    }
+    if topology == Topology::Torus {
+        flags |= FLAG_TORUS;
+    }
+    flags
+}
+
+/// Unpack field flags; None if unknown bits are set.
+pub(crate) fn decode_flags(flags: u8) -> Option<(DecayModel, Topology, Boundary)> {
+    if flags & !(FLAG_EXPONENTIAL | FLAG_OPEN | FLAG_TORUS) != 0 {
+        return None;
+    }
+    let model = if flags & FLAG_EXPONENTIAL != 0 {
+        DecayModel::Exponential
+    } else {
+        DecayModel::Linear
+    };
+    let topology = if flags & FLAG_TORUS != 0 {
+        Topology::Torus
+    } else {
+        Topology::Plane
+    };
+    let boundary = if flags & FLAG_OPEN != 0 {
+        Boundary::Open
+    } else {
+        Boundary::Closed
+    };
+    Some((model, topology, boundary))
+}
+
+impl FieldHeader {
+    fn write<W: Write>(&self, out: &mut W) -> Result<(), SnapshotError> {
+        out.write_all(&FIELD_MAGIC)?;
+        out.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        let flags = encode_flags(self.decay_model, self.topology, self.boundary);
+        out.write_all(&[self.layout, flags])?;
+        write_u32(out, dimension(self.width)?)?;
+        write_u32(out, dimension(self.height)?)?;
+        write_u32(out, self.decay_rate)?;
+        let t = self.thresholds;
+        for v in [t.solid_enter, t.solid_exit, t.liquid_enter, t.liquid_exit] {
+            write_u32(out, v)?;
+        }
+        Ok(())
+    }
+
+    fn read<R: Read>(input: &mut R, expected: u8) -> Result<Self, SnapshotError> {
+        read_preamble(input, FIELD_MAGIC)?;
+        let mut tag = [0u8; 2];
+        input.read_exact(&mut tag)?;
+        if tag[0] != expected {
+            return Err(SnapshotError::LayoutMismatch {
+                expected,
+                found: tag[0],
+            });
+        }
+        let (decay_model, topology, boundary) =
+            decode_flags(tag[1]).ok_or(SnapshotError::Corrupt("unknown field flags"))?;
+
+        let width = read_u32(input)? as usize;
+        let height = read_u32(input)? as usize;
+        let decay_rate = read_u32(input)?;
+        let thresholds = RegimeThresholds {
+            solid_enter: read_u32(input)?,
+            solid_exit: read_u32(input)?,
+            liquid_enter: read_u32(input)?,
+            liquid_exit: read_u32(input)?,
+        };
+        check_grid(width, height, decay_model, decay_rate, thresholds).map_err(invalid)?;
+        Ok(Self {
+            layout: tag[0],
+            width,
+            height,
+            decay_rate,
+            decay_model,
+            topology,
+            boundary,
+            thresholds,
+        })
+    }
+}
+
+impl Snapshot for DensityGrid {
+    fn write_snapshot<W: Write>(&self, out: &mut W) -> Result<(), SnapshotError> {
+        let (width, height) = self.dimensions();
+        FieldHeader {
+            layout: LAYOUT_DENSITY,
+            width,
+            height,
+            decay_rate: self.decay_rate(),
+            decay_model: self.decay_model(),
+            topology: self.topology(),
+            boundary: self.boundary(),
+            thresholds: self.regime_thresholds(),
+        }
+        .write(out)?;
+
+        for y in 0..height {
+            for x in 0..width {
+                write_u32(out, self.density(x, y))?;
+            }
+        }
+        write_regimes(out, self)
+    }
+
+    fn read_snapshot<R: Read>(input: &mut R) -> Result<Self, SnapshotError> {
+        let header = FieldHeader::read(input, LAYOUT_DENSITY)?;
+        let count = header.width * header.height;
+        let cells = read_bytes(input, count * 4)?;
+        let regimes = read_regimes(input, count)?;
+        let mut grid = DensityGrid::with_regimes(
+            header.width,
+            header.height,
+            header.decay_rate,
+            header.thresholds,
+        );
+        grid.set_decay_model(header.decay_model);
+        grid.set_topology(header.topology, header.boundary);
+        for (i, (cell, regime)) in cells.chunks_exact(4).zip(regimes).enumerate() {
+            grid.restore(i % header.width, i / header.width, word(cell), regime);
+        }
+        Ok(grid)
+    }
+}
+
+impl Snapshot for IsotopeGrid {
+    fn write_snapshot<W: Write>(&self, out: &mut W) -> Result<(), SnapshotError> {
+        let (width, height) = self.dimensions();
+        FieldHeader {
+            layout: LAYOUT_ISOTOPE,
+            width,
+            height,
+            decay_rate: self.decay_rate(),
+            decay_model: self.decay_model(),
+            topology: self.topology(),
+            boundary: self.boundary(),
+            thresholds: self.regime_thresholds(),
+        }
+        .write(out)?;
+
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g, b) = self.rgb(x, y);
+                write_u32(out, r)?;
+                write_u32(out, g)?;
+                write_u32(out, b)?;
+            }
+        }
+        write_regimes(out, self)
+    }
+
+    fn read_snapshot<R: Read>(input: &mut R) -> Result<Self, SnapshotError> {
+        let header = FieldHeader::read(input, LAYOUT_ISOTOPE)?;
+        let count = header.width * header.height;
+        let cells = read_bytes(input, count * 12)?;
+        let regimes = read_regimes(input, count)?;
+        let mut grid = IsotopeGrid::with_regimes(
+            header.width,
+            header.height,
+            header.decay_rate,
+            header.thresholds,
+        );
+        grid.set_decay_model(header.decay_model);
+        grid.set_topology(header.topology, header.boundary);
+        for (i, (cell, regime)) in cells.chunks_exact(12).zip(regimes).enumerate() {
+            let rgb = (word(cell), word(&cell[4..]), word(&cell[8..]));
+            rgb.0
+                .checked_add(rgb.1)
+                .and_then(|rg| rg.checked_add(rgb.2))
+                .ok_or(SnapshotError::Corrupt("cell density overflows"))?;
+            grid.restore(i % header.width, i / header.width, rgb, regime);
+        }
+        Ok(grid)
+    }
+}
+
+/// Snapshots the space and tick count; living shapes are not recorded.
+impl<P, F: TraceField + Snapshot> Snapshot for Substrate<P, F> {
+    fn write_snapshot<W: Write>(&self, out: &mut W) -> Result<(), SnapshotError> {
+        out.write_all(&SUBSTRATE_MAGIC)?;
+        out.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        out.write_all(&[0, 0])?;
+        out.write_all(&self.tick_count().to_le_bytes())?;
+        write_u32(out, self.space().threshold())?;
+        self.space().field().write_snapshot(out)
+    }
+
+    fn read_snapshot<R: Read>(input: &mut R) -> Result<Self, SnapshotError> {
+        read_preamble(input, SUBSTRATE_MAGIC)?;
+        let mut reserved = [0u8; 2];
+        input.read_exact(&mut reserved)?;
+        if reserved != [0, 0] {
+            return Err(SnapshotError::Corrupt("reserved header bytes set"));
+        }
+        let mut tick = [0u8; 8];
+        input.read_exact(&mut tick)?;
+        let threshold = read_u32(input)?;
+        let field = F::read_snapshot(input)?;
+        check_threshold(threshold, field.regime_thresholds()).map_err(invalid)?;
+
+        let mut substrate = Substrate::from_space(Space::from_field(field, threshold));
+        substrate.set_tick_count(u64::from_le_bytes(tick));
+        Ok(substrate)
+    }
+}
+
+/// Check magic and version.
+fn read_preamble<R: Read>(input: &mut R, magic: [u8; 4]) -> Result<(), SnapshotError> {
+    let mut found = [0u8; 4];
+    input.read_exact(&mut found)?;
+    if found != magic {
+        return Err(SnapshotError::BadMagic);
+    }
+    let mut version = [0u8; 2];
+    input.read_exact(&mut version)?;
+    match u16::from_le_bytes(version) {
+        SNAPSHOT_VERSION => Ok(()),
+        v => Err(SnapshotError::UnsupportedVersion(v)),
+    }
+}
+
+/// Header values a validated constructor would reject.
+fn invalid(e: TesError) -> SnapshotError {
+    SnapshotError::Corrupt(match e {
+        TesError::ZeroDimension => "zero dimensions",
+        TesError::TooLarge { .. } => "dimensions too large",
+        TesError::InvertedThresholds(_) => "inverted regime thresholds",
+        TesError::ThresholdOutOfRange { .. } => "threshold outside the regime boundaries",
+        _ => "decay rate out of range",
+    })
+}
+
+/// Write the regime section of `field`, row-major.
+fn write_regimes<W: Write, F: TraceField>(out: &mut W, field: &F) -> Result<(), SnapshotError> {
+    let (width, height) = field.dimensions();
+    let regimes: Vec<u8> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| field.regime(x, y) as u8)
+        .collect();
+    out.write_all(&regimes)?;
+    Ok(())
+}
+
+/// Read the regime section of `count` cells.
+fn read_regimes<R: Read>(input: &mut R, count: usize) -> Result<Vec<Regime>, SnapshotError> {
+    read_bytes(input, count)?
+        .into_iter()
+        .map(|v| Regime::decode(v).ok_or(SnapshotError::Corrupt("unknown cell regime")))
+        .collect()
+}
+
+/// Read a section of `len` bytes.
+///
+/// Memory grows with the bytes actually read, so a corrupt header cannot
+/// trigger a huge allocation; the grid is only allocated once its cells
+/// are in.
+fn read_bytes<R: Read>(input: &mut R, len: usize) -> Result<Vec<u8>, SnapshotError> {
+    let mut bytes = Vec::new();
+    input.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(SnapshotError::Truncated);
+    }
+    Ok(bytes)
+}
+
+/// First little-endian u32 of `bytes`.
+fn word(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn dimension(v: usize) -> Result<u32, SnapshotError> {
+    u32::try_from(v).map_err(|_| SnapshotError::Corrupt("dimension exceeds u32"))
+}
+
+fn write_u32<W: Write>(out: &mut W, v: u32) -> Result<(), SnapshotError> {
+    out.write_all(&v.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u32<R: Read>(input: &mut R) -> Result<u32, SnapshotError> {
+    let mut bytes = [0u8; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isotope::ServiceColor;
+
+    #[test]
+    fn test_density_round_trip() {
+        let thresholds = RegimeThresholds::with_margin(1000, 500, 50);
+        let grid = DensityGrid::with_regimes(6, 4, 7, thresholds);
+        grid.contribute(1, 2, 640);
+        grid.contribute(5, 3, 1200);
+
+        let bytes = grid.to_snapshot();
+        assert_eq!(bytes.len(), 36 + 6 * 4 * (4 + 1));
+
+        let restored = DensityGrid::from_snapshot(&bytes).unwrap();
+        assert_eq!(restored.dimensions(), (6, 4));
+        assert_eq!(restored.decay_rate(), 7);
+        assert_eq!(restored.regime_thresholds(), thresholds);
+        assert_eq!(restored.density(1, 2), 640);
+        assert_eq!(restored.regime(5, 3), Regime::Solid);
+    }
+
+    #[test]
+    fn test_isotope_round_trip_keeps_channels() {
+        let grid = IsotopeGrid::new(4, 4, 3, 1000, 500);
+        grid.contribute(2, 2, 300, ServiceColor::red());
+        grid.contribute(2, 2, 90, ServiceColor::neutral());
+
+        let restored = IsotopeGrid::from_snapshot(&grid.to_snapshot()).unwrap();
+        assert_eq!(restored.rgb(2, 2), grid.rgb(2, 2));
+        assert_eq!(restored.density(2, 2), grid.density(2, 2));
+
+        // Decay continues from the restored terrain
+        grid.apply_decay();
+        restored.apply_decay();
+        assert_eq!(restored.rgb(2, 2), grid.rgb(2, 2));
+        assert_eq!(restored.density(2, 2), grid.density(2, 2));
+    }
+
+    #[test]
+    fn test_lattice_settings_round_trip() {
+        let mut grid = DensityGrid::new(3, 3, 100, 1000, 500);
+        grid.set_decay_model(DecayModel::Exponential);
+        grid.set_topology(Topology::Torus, Boundary::Open);
+
+        let mut bytes = grid.to_snapshot();
+        assert_eq!(bytes[7], FLAG_EXPONENTIAL | FLAG_OPEN | FLAG_TORUS);
+        let restored = DensityGrid::from_snapshot(&bytes).unwrap();
+        assert_eq!(restored.decay_model(), DecayModel::Exponential);
+        assert_eq!(restored.topology(), Topology::Torus);
+        assert_eq!(restored.boundary(), Boundary::Open);
+
+        bytes[7] = 0x80;
+        assert!(matches!(
+            DensityGrid::from_snapshot(&bytes),
+            Err(SnapshotError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_substrate_resumes_terrain_without_shapes() {
+        let mut sub = Substrate::new(8, 8, 1, 100);
+        sub.spawn(3, 3, 50, 30).unwrap();
+        sub.run(5);
+
+        let restored: Substrate = Substrate::from_snapshot(&sub.to_snapshot()).unwrap();
+        assert_eq!(restored.tick_count(), 5);
+        assert_eq!(restored.space().threshold(), 100);
+        assert_eq!(restored.density_map(), sub.density_map());
+        assert_eq!(restored.shape_count(), 0);
+
+        // The saturated cell still rejects after restart
+        let mut restored = restored;
+        assert!(restored.spawn(3, 3, 10, 1).is_none());
+    }
+
+    #[test]
+    fn test_keeps_cells_held_solid() {
+        let grid = IsotopeGrid::with_regimes(2, 1, 10, RegimeThresholds::with_margin(100, 50, 20));
+        grid.contribute(0, 0, 101, ServiceColor::red());
+        grid.apply_decay();
+        assert_eq!((grid.density(0, 0), grid.regime(0, 0)), (91, Regime::Solid));
+
+        let restored = IsotopeGrid::from_snapshot(&grid.to_snapshot()).unwrap();
+        assert_eq!(restored.regime(0, 0), Regime::Solid);
+        assert!(!restored.is_habitable(0, 0, 100));
+
+        let mut bytes = grid.to_snapshot();
+        *bytes.last_mut().unwrap() = 7;
+        assert!(matches!(
+            IsotopeGrid::from_snapshot(&bytes),
+            Err(SnapshotError::Corrupt("unknown cell regime"))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_invalid_substrate_header() {
+        let sub: Substrate = Substrate::new(4, 4, 1, 100);
+        let bytes = sub.to_snapshot();
+
+        let mut reserved = bytes.clone();
+        reserved[6] = 1;
+        assert!(matches!(
+            Substrate::<(), DensityGrid>::from_snapshot(&reserved),
+            Err(SnapshotError::Corrupt("reserved header bytes set"))
+        ));
+
+        for threshold in [0u32, 101] {
+            let mut bad = bytes.clone();
+            bad[16..20].copy_from_slice(&threshold.to_le_bytes());
+            assert!(matches!(
+                Substrate::<(), DensityGrid>::from_snapshot(&bad),
+                Err(SnapshotError::Corrupt(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        let grid = DensityGrid::new(2, 2, 0, 1000, 500);
+        let bytes = grid.to_snapshot();
+
+        assert!(matches!(
+            IsotopeGrid::from_snapshot(&bytes),
+            Err(SnapshotError::LayoutMismatch {
+                expected: 1,
+                found: 0
+            })
+        ));
+        assert!(matches!(
+            DensityGrid::from_snapshot(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Truncated)
+        ));
+        assert!(matches!(
+            DensityGrid::from_snapshot(b"nope"),
+            Err(SnapshotError::BadMagic)
+        ));
+
+        let mut future = bytes.clone();
+        future[4] = 9;
+        assert!(matches!(
+            DensityGrid::from_snapshot(&future),
+            Err(SnapshotError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            Substrate::<(), DensityGrid>::from_snapshot(&bytes),
+            Err(SnapshotError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn test_rejects_invalid_header_before_allocating() {
+        let grid = IsotopeGrid::new(2, 2, 0, 1000, 500);
+        let header = &grid.to_snapshot()[..36];
+        let with = |offset: usize, v: u32| {
+            let mut bytes = header.to_vec();
+            bytes[offset..offset + 4].copy_from_slice(&v.to_le_bytes());
+            bytes
+        };
+
+        // 65535 × 65535 cells claimed, a few bytes present
+        let mut huge = with(8, 65535);
+        huge[12..16].copy_from_slice(&65535u32.to_le_bytes());
+        huge.extend_from_slice(&[0; 24]);
+        assert!(matches!(
+            IsotopeGrid::from_snapshot(&huge),
+            Err(SnapshotError::Truncated)
+        ));
+
+        for (bytes, what) in [
+            (with(8, 0), "zero dimensions"),
+            (with(28, 2000), "inverted regime thresholds"),
+            (with(16, 5000), "decay rate out of range"),
+        ] {
+            match IsotopeGrid::from_snapshot(&bytes) {
+                Err(SnapshotError::Corrupt(found)) => assert_eq!(found, what),
+                other => panic!("expected {:?}, got {:?}", what, other.err()),
+            }
+        }
+    }
+}
@@ -2,7 +2,13 @@
 //!
 //! Space does NOT change. Observations project through time.
 
-use crate::grid::DensityGrid;
+use crate::error::TesError;
+use crate::field::TraceField;
+use crate::footprint::{Footprint, Habitability};
+use crate::grid::{DensityGrid, Regime, RegimeThresholds};
+use crate::isotope::{IsotopeGrid, ServiceColor};
+use crate::shape::Shape;
+use crate::stats::FieldStats;
 
 /// The topographic execution space.
 ///
@@ -16,9 +22,12 @@ use crate::grid::DensityGrid;
 /// - `ω` → density values in grid (weight function)
 /// - `δ` → `apply_decay()` (projection function)
 /// - `τ` → habitability threshold (local lifetime function)
-pub struct Space {
-    /// Trace density grid
-    pub(crate) trace: DensityGrid,
+///
+/// `ω` is stored in any [`TraceField`] layout; by default a monochrome
+/// [`DensityGrid`].
+pub struct Space<F = DensityGrid> {
+    /// Trace density field
+    pub(crate) trace: F,
     /// Global habitability threshold
     threshold: u32,
 }
@@ -32,6 +41,10 @@ impl Space {
     /// * `height` - Space height
     /// * `decay_rate` - Trace decay per tick
     /// * `threshold` - Habitability threshold
+    ///
+    /// The Solid boundary is `threshold` and the Liquid boundary
+    /// `threshold / 2`; use [`Space::try_new`] or [`Space::builder`] to set
+    /// them explicitly.
     pub fn new(width: usize, height: usize, decay_rate: u32, threshold: u32) -> Self {
         let solid = threshold;
         let liquid = threshold / 2;
@@ -39,14 +52,115 @@ impl Space {
         Self { trace, threshold }
     }
 
+    /// Create a validated space with explicit regime boundaries.
+    ///
+    /// The habitability threshold is the Solid entry boundary.
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        let trace = DensityGrid::try_with_regimes(width, height, decay_rate, thresholds)?;
+        Ok(Self::from_field(trace, thresholds.solid_enter))
+    }
+}
+
+impl Space<IsotopeGrid> {
+    /// Create a new space backed by an isotope (RGB) field.
+    ///
+    /// Same parameters as [`Space::new`]; habitability and regimes are
+    /// evaluated on total density.
+    pub fn with_isotopes(width: usize, height: usize, decay_rate: u32, threshold: u32) -> Self {
+        let solid = threshold;
+        let liquid = threshold / 2;
+        let trace = IsotopeGrid::new(width, height, decay_rate, solid, liquid);
+        Self { trace, threshold }
+    }
+
+    /// Create a validated isotope space (see [`Space::try_new`]).
+    pub fn try_with_isotopes(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        let trace = IsotopeGrid::try_with_regimes(width, height, decay_rate, thresholds)?;
+        Ok(Self::from_field(trace, thresholds.solid_enter))
+    }
+}
+
+impl<F: TraceField> Space<F> {
+    /// Create a space over an existing trace field.
+    pub fn from_field(trace: F, threshold: u32) -> Self {
+        Self { trace, threshold }
+    }
+
     /// Check if position is habitable.
     ///
-    /// From A1: `inhabits(s, space) ⟺ ... ∧ ω(pos(s)) < threshold(space)`
+    /// From A1: `inhabits(s, space) ⟺ ... ∧ ω(pos(s)) < threshold(space)`.
+    /// A cell that settled Solid stays uninhabitable until it falls to the
+    /// Solid exit boundary (see [`RegimeThresholds`]).
     #[inline]
     pub fn is_habitable(&self, x: usize, y: usize) -> bool {
         self.trace.is_habitable(x, y, self.threshold)
     }
 
+    /// Check if a footprint centered at position is habitable.
+    ///
+    /// Cells off a bounded plane are ignored; on a torus they wrap. Cells
+    /// held Solid count as saturated in either mode.
+    pub fn is_habitable_footprint(
+        &self,
+        x: usize,
+        y: usize,
+        footprint: &Footprint,
+        mode: Habitability,
+    ) -> bool {
+        if *footprint == Footprint::Point {
+            return self.is_habitable(x, y);
+        }
+        self.is_habitable_kernel(x, y, &footprint.kernel(), mode)
+    }
+
+    /// Check if a shape's footprint is habitable at its position.
+    pub(crate) fn admits<P>(&self, shape: &Shape<P>, mode: Habitability) -> bool {
+        let (x, y) = shape.pos;
+        if shape.footprint == Footprint::Point {
+            return self.is_habitable(x, y);
+        }
+        self.is_habitable_kernel(x, y, &shape.kernel(), mode)
+    }
+
+    fn is_habitable_kernel(
+        &self,
+        x: usize,
+        y: usize,
+        kernel: &[(isize, isize, f32)],
+        mode: Habitability,
+    ) -> bool {
+        let (dims, topology) = (self.dimensions(), self.trace.topology());
+        let mut cells = kernel.iter().filter_map(|&(dx, dy, weight)| {
+            topology
+                .offset(dims, x, y, dx, dy)
+                .map(|(cx, cy)| (cx, cy, weight))
+        });
+
+        match mode {
+            Habitability::Max => cells.all(|(cx, cy, _)| self.is_habitable(cx, cy)),
+            Habitability::Mean => {
+                let (sum, weight) = cells.fold((0.0, 0.0), |(sum, total), (cx, cy, weight)| {
+                    let d = match self.regime(cx, cy) {
+                        Regime::Solid => self.density(cx, cy).max(self.threshold),
+                        _ => self.density(cx, cy),
+                    };
+                    (sum + d as f32 * weight, total + weight)
+                });
+                weight == 0.0 || sum / weight < self.threshold as f32
+            }
+        }
+    }
+
     /// Get density at position.
     #[inline]
     pub fn density(&self, x: usize, y: usize) -> u32 {
@@ -55,16 +169,78 @@ impl Space {
 
     /// Get regime at position.
     #[inline]
-    pub fn regime(&self, x: usize, y: usize) -> crate::grid::Regime {
+    pub fn regime(&self, x: usize, y: usize) -> Regime {
         self.trace.regime(x, y)
     }
 
     /// Contribute trace at position (side-effect of presence).
+    ///
+    /// On an isotope space the trace is uncolored (evenly split).
     #[inline]
     pub fn contribute(&self, x: usize, y: usize, amount: u32) {
         self.trace.contribute(x, y, amount);
     }
 
+    /// Contribute trace carrying a service color signature.
+    ///
+    /// The color is only observable on an isotope space.
+    #[inline]
+    pub fn contribute_colored(&self, x: usize, y: usize, amount: u32, color: ServiceColor) {
+        self.trace.contribute_colored(x, y, amount, color);
+    }
+
+    /// Contribute trace only if position is habitable (atomic A1 check).
+    ///
+    /// Returns `false` without contributing if the position is saturated.
+    #[inline]
+    pub fn try_contribute(&self, x: usize, y: usize, amount: u32, color: ServiceColor) -> bool {
+        self.trace
+            .try_contribute_colored(x, y, amount, color, self.threshold)
+    }
+
+    /// Contribute trace spread over a footprint centered at position.
+    ///
+    /// Shares falling off a bounded plane are lost; on a torus they wrap.
+    pub fn contribute_footprint(
+        &self,
+        x: usize,
+        y: usize,
+        amount: u32,
+        footprint: &Footprint,
+        color: ServiceColor,
+    ) {
+        if *footprint == Footprint::Point {
+            self.contribute_colored(x, y, amount, color);
+            return;
+        }
+        self.contribute_shares(x, y, &footprint.distribute(amount), color);
+    }
+
+    /// Contribute a shape's trace over its footprint.
+    pub(crate) fn contribute_shape<P>(&self, shape: &Shape<P>) {
+        let (x, y) = shape.pos;
+        if shape.footprint == Footprint::Point {
+            self.contribute_colored(x, y, shape.contribution, shape.color);
+            return;
+        }
+        self.contribute_shares(x, y, &shape.shares(), shape.color);
+    }
+
+    fn contribute_shares(
+        &self,
+        x: usize,
+        y: usize,
+        shares: &[(isize, isize, u32)],
+        color: ServiceColor,
+    ) {
+        let (dims, topology) = (self.dimensions(), self.trace.topology());
+        for &(dx, dy, share) in shares {
+            if let Some((cx, cy)) = topology.offset(dims, x, y, dx, dy) {
+                self.contribute_colored(cx, cy, share, color);
+            }
+        }
+    }
+
     /// Apply decay projection.
     ///
     /// This is δ: (ω, t) → ω'
@@ -73,13 +249,28 @@ impl Space {
         self.trace.apply_decay();
     }
 
+    /// Apply one diffusion step to the trace field.
+    pub fn diffuse(&self) {
+        self.trace.diffuse();
+    }
+
     /// Get dimensions.
     pub fn dimensions(&self) -> (usize, usize) {
         self.trace.dimensions()
     }
 
+    /// Get the underlying trace field.
+    pub fn field(&self) -> &F {
+        &self.trace
+    }
+
     /// Get threshold.
     pub fn threshold(&self) -> u32 {
         self.threshold
     }
+
+    /// Compute global field statistics.
+    pub fn stats(&self) -> FieldStats {
+        self.trace.stats()
+    }
 }
@@ -0,0 +1,188 @@
+//! Field statistics - the telemetry band
+//!
+//! One pass over the field yields the global metrics of docs/gui.md
+//! (`ENTROPY | PHASE | SOLID% | DECAY`) plus mass and density extremes.
+//!
+//! Statistics describe the field as a whole (Source Amnesia): nothing here
+//! can be attributed to an individual shape.
+
+use crate::field::TraceField;
+use crate::grid::Regime;
+
+/// Global metrics of a trace field at one instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldStats {
+    /// Number of cells
+    pub cells: usize,
+    /// Sum of all densities
+    pub total_mass: u64,
+    /// Shannon entropy (bits) of the field normalized to a distribution
+    ///
+    /// 0 for an empty field or all mass in one cell, `log2(cells)` for a
+    /// uniform field.
+    pub entropy: f64,
+    /// Cells in Solid regime
+    pub solid: usize,
+    /// Cells in Liquid regime
+    pub liquid: usize,
+    /// Cells in Gas regime
+    pub gas: usize,
+    /// Highest cell density
+    pub max_density: u32,
+    /// Accepted spawns (0 when computed from a bare field)
+    pub accepted: u64,
+    /// Rejected spawns (0 when computed from a bare field)
+    pub rejected: u64,
+}
+
+impl FieldStats {
+    /// Compute statistics of `field` in a single pass.
+    pub fn of<F: TraceField + ?Sized>(field: &F) -> Self {
+        let (w, h) = field.dimensions();
+        let mut stats = Self {
+            cells: w * h,
+            total_mass: 0,
+            entropy: 0.0,
+            solid: 0,
+            liquid: 0,
+            gas: 0,
+            max_density: 0,
+            accepted: 0,
+            rejected: 0,
+        };
+
+        // Σ d·log2(d), so entropy needs no second pass:
+        // H = -Σ p·log2(p) = log2(T) - Σ d·log2(d) / T
+        let mut d_log_d = 0.0f64;
+        for y in 0..h {
+            for x in 0..w {
+                let d = field.density(x, y);
+                stats.total_mass += d as u64;
+                stats.max_density = stats.max_density.max(d);
+                if d > 0 {
+                    let d = d as f64;
+                    d_log_d += d * d.log2();
+                }
+                match field.regime(x, y) {
+                    Regime::Solid => stats.solid += 1,
+                    Regime::Liquid => stats.liquid += 1,
+                    Regime::Gas => stats.gas += 1,
+                }
+            }
+        }
+
+        if stats.total_mass > 0 {
+            let total = stats.total_mass as f64;
+            stats.entropy = (total.log2() - d_log_d / total).max(0.0);
+        }
+        stats
+    }
+
+    /// Mean cell density.
+    pub fn mean_density(&self) -> f64 {
+        if self.cells == 0 {
+            return 0.0;
+        }
+        self.total_mass as f64 / self.cells as f64
+    }
+
+    /// Percentage of cells in Solid regime (SOLID%).
+    pub fn solid_pct(&self) -> f32 {
+        self.pct(self.solid)
+    }
+
+    /// Percentage of cells in Liquid regime.
+    pub fn liquid_pct(&self) -> f32 {
+        self.pct(self.liquid)
+    }
+
+    /// Percentage of cells in Gas regime.
+    pub fn gas_pct(&self) -> f32 {
+        self.pct(self.gas)
+    }
+
+    /// Regime covering the largest area (PHASE).
+    ///
+    /// Ties resolve toward the denser regime.
+    pub fn phase(&self) -> Regime {
+        if self.solid >= self.liquid && self.solid >= self.gas {
+            Regime::Solid
+        } else if self.liquid >= self.gas {
+            Regime::Liquid
+        } else {
+            Regime::Gas
+        }
+    }
+
+    /// Fraction of spawns rejected (0 if none were attempted).
+    pub fn rejection_rate(&self) -> f32 {
+        let attempted = self.accepted + self.rejected;
+        if attempted == 0 {
+            return 0.0;
+        }
+        self.rejected as f32 / attempted as f32
+    }
+
+    fn pct(&self, count: usize) -> f32 {
+        if self.cells == 0 {
+            return 0.0;
+        }
+        count as f32 * 100.0 / self.cells as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::DensityGrid;
+    use crate::isotope::{IsotopeGrid, ServiceColor};
+
+    #[test]
+    fn test_empty_field() {
+        let stats = FieldStats::of(&DensityGrid::new(4, 4, 0, 1000, 500));
+
+        assert_eq!(stats.cells, 16);
+        assert_eq!(stats.total_mass, 0);
+        assert_eq!(stats.entropy, 0.0);
+        assert_eq!(stats.gas_pct(), 100.0);
+        assert_eq!(stats.phase(), Regime::Gas);
+        assert_eq!(stats.mean_density(), 0.0);
+        assert_eq!(stats.rejection_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_entropy_bounds() {
+        // All mass in one cell: fully ordered
+        let grid = DensityGrid::new(4, 4, 0, 1000, 500);
+        grid.contribute(1, 1, 800);
+        assert_eq!(FieldStats::of(&grid).entropy, 0.0);
+
+        // Uniform field: maximal entropy, log2(16) = 4 bits
+        let grid = DensityGrid::new(4, 4, 0, 1000, 500);
+        for y in 0..4 {
+            for x in 0..4 {
+                grid.contribute(x, y, 100);
+            }
+        }
+        let stats = FieldStats::of(&grid);
+        assert!((stats.entropy - 4.0).abs() < 1e-9);
+        assert_eq!(stats.mean_density(), 100.0);
+    }
+
+    #[test]
+    fn test_regime_areas_and_phase() {
+        let grid = IsotopeGrid::new(2, 2, 0, 1000, 500);
+        grid.contribute(0, 0, 1200, ServiceColor::red());
+        grid.contribute(1, 0, 600, ServiceColor::green());
+        grid.contribute(0, 1, 700, ServiceColor::blue());
+
+        let stats = FieldStats::of(&grid);
+        assert_eq!(stats.total_mass, 2500);
+        assert_eq!(stats.max_density, 1200);
+        assert_eq!(stats.solid_pct(), 25.0);
+        assert_eq!(stats.liquid_pct(), 50.0);
+        assert_eq!(stats.gas_pct(), 25.0);
+        assert_eq!(stats.phase(), Regime::Liquid);
+    }
+}
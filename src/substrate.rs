@@ -4,84 +4,341 @@
 
 use crate::space::Space;
 use crate::shape::Shape;
+use crate::relation::Relation;
+use crate::accounting::SpawnStats;
+use crate::event::{CellTracker, Event, Observer};
+use crate::field::TraceField;
+use crate::footprint::{Footprint, Habitability};
+use crate::error::TesError;
+use crate::grid::{DensityGrid, RegimeThresholds};
+use crate::isotope::{IsotopeGrid, ServiceColor};
+use crate::stats::FieldStats;
 
 /// The TES substrate - combines space and shapes.
-pub struct Substrate {
+///
+/// Each shape hosts an owned payload `P`. When a shape dies its payload is
+/// dropped, unless [`tick_with`](Substrate::tick_with) is used to hand it
+/// back (death = drop).
+///
+/// The space can be backed by any [`TraceField`]; by default a monochrome
+/// [`DensityGrid`].
+pub struct Substrate<P = (), F = DensityGrid> {
     /// The topographic space
-    space: Space,
+    space: Space<F>,
     /// All shapes in the substrate
-    shapes: Vec<Shape>,
+    shapes: Vec<Shape<P>>,
     /// Current tick count
     tick_count: u64,
     /// Next shape ID
     next_id: u64,
+    /// How shape relationality is evaluated (A0)
+    relation: Relation,
+    /// Explicit links between living shapes
+    links: Vec<(u64, u64)>,
+    /// How habitability is evaluated over footprints (A1)
+    habitability: Habitability,
+    /// Registered observers
+    observers: Vec<Box<dyn Observer + Send>>,
+    /// Per-cell state for regime/saturation events (only while observed)
+    tracker: Option<CellTracker>,
+    /// Ticks a new cell state must hold before it is reported
+    event_hysteresis: u32,
+    /// Events collected for the current tick
+    events: Vec<Event>,
+    /// Spawn acceptance/rejection accounting (A1)
+    spawns: SpawnStats,
 }
 
-impl Substrate {
+impl<P> Substrate<P> {
     /// Create a new substrate.
     pub fn new(width: usize, height: usize, decay_rate: u32, threshold: u32) -> Self {
+        Self::from_space(Space::new(width, height, decay_rate, threshold))
+    }
+
+    /// Create a validated substrate (see [`Space::try_new`]).
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        Space::try_new(width, height, decay_rate, thresholds).map(Self::from_space)
+    }
+}
+
+impl<P> Substrate<P, IsotopeGrid> {
+    /// Create a new substrate running on an isotope (RGB) field.
+    ///
+    /// Shapes contribute trace in their service color, so the full lifecycle
+    /// can be analyzed spectroscopically via [`Space::field`].
+    pub fn with_isotopes(width: usize, height: usize, decay_rate: u32, threshold: u32) -> Self {
+        Self::from_space(Space::with_isotopes(width, height, decay_rate, threshold))
+    }
+
+    /// Create a validated isotope substrate (see [`Space::try_new`]).
+    pub fn try_with_isotopes(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        thresholds: RegimeThresholds,
+    ) -> Result<Self, TesError> {
+        Space::try_with_isotopes(width, height, decay_rate, thresholds).map(Self::from_space)
+    }
+}
+
+impl<P, F: TraceField> Substrate<P, F> {
+    /// Create a substrate over an existing space.
+    pub fn from_space(space: Space<F>) -> Self {
         Self {
-            space: Space::new(width, height, decay_rate, threshold),
+            space,
             shapes: Vec::new(),
             tick_count: 0,
             next_id: 1,
+            relation: Relation::default(),
+            links: Vec::new(),
+            habitability: Habitability::default(),
+            observers: Vec::new(),
+            tracker: None,
+            event_hysteresis: 1,
+            events: Vec::new(),
+            spawns: SpawnStats::default(),
         }
     }
 
-    /// Spawn a new shape at position.
+    /// Register an observer for topography events.
     ///
-    /// Returns the shape ID, or None if position is not habitable.
-    pub fn spawn(
+    /// Observers receive all events of a tick in one batch, at the end of
+    /// that tick. Spawn rejections between ticks are reported with the
+    /// next tick. Cell tracking starts from the current state, so existing
+    /// terrain produces no events.
+    pub fn add_observer<O>(&mut self, observer: O)
+    where
+        O: Observer + Send + 'static,
+    {
+        if self.tracker.is_none() {
+            self.tracker = Some(CellTracker::new(&self.space, self.event_hysteresis));
+        }
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Set how many consecutive ticks a new regime or saturation state must
+    /// hold before an event is emitted (default 1: report immediately).
+    ///
+    /// Suppresses flapping of cells hovering around a threshold.
+    pub fn set_event_hysteresis(&mut self, ticks: u32) {
+        self.event_hysteresis = ticks.max(1);
+        if let Some(tracker) = &mut self.tracker {
+            tracker.set_hysteresis(self.event_hysteresis);
+        }
+    }
+
+    /// Break spawn accounting down by square regions of `size` cells.
+    pub fn track_spawn_regions(&mut self, size: usize) {
+        let (w, h) = self.space.dimensions();
+        self.spawns.track_regions(w, h, size);
+    }
+
+    /// Break spawn accounting down by service color.
+    pub fn track_spawn_colors(&mut self) {
+        self.spawns.track_colors();
+    }
+
+    /// Keep a heatmap of rejected spawns, dimmed by `fade_step` each tick.
+    pub fn track_rejections(&mut self, fade_step: u8) {
+        let (w, h) = self.space.dimensions();
+        self.spawns.track_heatmap(w, h, fade_step);
+    }
+
+    /// Get spawn acceptance statistics.
+    pub fn spawn_stats(&self) -> &SpawnStats {
+        &self.spawns
+    }
+
+    /// Set how habitability is evaluated over multi-cell footprints.
+    pub fn set_habitability(&mut self, habitability: Habitability) {
+        self.habitability = habitability;
+    }
+
+    /// Set how relationality is evaluated (A0).
+    ///
+    /// With anything other than `Relation::Unconditional`, isolated shapes
+    /// keep living but deposit no trace.
+    pub fn set_relation(&mut self, relation: Relation) {
+        self.relation = relation;
+    }
+
+    /// Get relation mode.
+    pub fn relation(&self) -> Relation {
+        self.relation
+    }
+
+    /// Tag a living shape with a group (for `Relation::Group`).
+    ///
+    /// Returns `false` if no living shape has this ID.
+    pub fn set_group(&mut self, id: u64, group: u64) -> bool {
+        match self.shapes.iter_mut().find(|s| s.id == id) {
+            Some(shape) => {
+                shape.group = Some(group);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Explicitly link two living shapes (for `Relation::Link`).
+    ///
+    /// Links are dropped when either shape dies.
+    /// Returns `false` if either shape is not alive.
+    pub fn link(&mut self, a: u64, b: u64) -> bool {
+        let alive = |id| self.shapes.iter().any(|s| s.id == id);
+        if a == b || !alive(a) || !alive(b) {
+            return false;
+        }
+        self.links.push((a, b));
+        true
+    }
+
+    /// Spawn a new shape hosting `payload` at position.
+    ///
+    /// Returns the shape ID, or hands the payload back if position is not
+    /// habitable.
+    pub fn spawn_with(
         &mut self,
         x: usize,
         y: usize,
         lifetime: u32,
         contribution: u32,
-    ) -> Option<u64> {
-        // A1: Check habitability
-        if !self.space.is_habitable(x, y) {
-            return None;
+        payload: P,
+    ) -> Result<u64, P> {
+        self.spawn_shaped(x, y, lifetime, contribution, Footprint::Point, payload)
+    }
+
+    /// Spawn a new shape whose contribution is spread over `footprint`.
+    ///
+    /// Habitability is evaluated over the whole footprint. A radius beyond
+    /// the larger grid dimension is clamped to it.
+    pub fn spawn_shaped(
+        &mut self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+        footprint: Footprint,
+        payload: P,
+    ) -> Result<u64, P> {
+        let mut shape = Shape::new(0, x, y, 100, lifetime, 1.0, contribution);
+        let (width, height) = self.space.dimensions();
+        shape.footprint = footprint.clamped(width.max(height));
+        self.admit(shape, payload)
+    }
+
+    /// Spawn a new shape contributing trace in `color`.
+    pub fn spawn_colored_with(
+        &mut self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+        color: ServiceColor,
+        payload: P,
+    ) -> Result<u64, P> {
+        let mut shape = Shape::new(0, x, y, 100, lifetime, 1.0, contribution);
+        shape.color = color;
+        self.admit(shape, payload)
+    }
+
+    /// Admit a prepared shape: check habitability (A1), assign its ID and
+    /// attach the payload.
+    fn admit(&mut self, mut shape: Shape<P>, payload: P) -> Result<u64, P> {
+        let (x, y) = shape.pos;
+        shape.build_kernel();
+        if !self.space.admits(&shape, self.habitability) {
+            self.spawns.record(x, y, shape.color, false);
+            if !self.observers.is_empty() {
+                self.events.push(Event::SpawnRejected { x, y });
+            }
+            return Err(payload);
         }
 
-        let id = self.next_id;
+        shape.id = self.next_id;
         self.next_id += 1;
+        self.spawns.record(x, y, shape.color, true);
+        shape.attach_payload(payload);
 
-        let mut shape = Shape::new(id, x, y, 100, lifetime, 1.0, contribution);
-        shape.attach_payload(); // Assume payload on spawn
-
+        let id = shape.id;
         self.shapes.push(shape);
-        Some(id)
+        Ok(id)
+    }
+
+    /// Set service color of a living shape.
+    ///
+    /// Returns `false` if no living shape has this ID.
+    pub fn set_color(&mut self, id: u64, color: ServiceColor) -> bool {
+        match self.shapes.iter_mut().find(|s| s.id == id) {
+            Some(shape) => {
+                shape.color = color;
+                true
+            }
+            None => false,
+        }
     }
 
     /// Run one tick of the simulation.
     ///
     /// This is the core loop:
-    /// 1. Each alive shape with payload contributes trace
-    /// 2. Each shape loses one tick of lifetime
-    /// 3. Dead shapes are removed
-    /// 4. Space decay is applied
+    /// 1. Relationality is evaluated (A0)
+    /// 2. Each alive, related shape with payload contributes trace
+    /// 3. Each shape loses one tick of lifetime
+    /// 4. Dead shapes are removed, dropping their payloads
+    /// 5. Space decay is applied
+    /// 6. Events are reported to observers
     pub fn tick(&mut self) {
+        self.tick_with(|_, _| {});
+    }
+
+    /// Run one tick, handing payloads of expired shapes to `on_expire`.
+    ///
+    /// `on_expire` receives the shape ID and its payload at the moment the
+    /// shape dies, instead of the payload being dropped.
+    pub fn tick_with<E>(&mut self, mut on_expire: E)
+    where
+        E: FnMut(u64, P),
+    {
         self.tick_count += 1;
+        let observed = !self.observers.is_empty();
+
+        // Phases 0-3: shape lifecycle
+        let events = &mut self.events;
+        advance_shapes(
+            &self.space,
+            &mut self.shapes,
+            self.relation,
+            &mut self.links,
+            &mut |id, payload| {
+                if observed {
+                    events.push(Event::ShapeDied { id });
+                }
+                if let Some(payload) = payload {
+                    on_expire(id, payload);
+                }
+            },
+        );
 
-        // Phase 1: Contribution (A3)
-        for shape in &self.shapes {
-            if shape.can_produce_trace() {
-                let (x, y) = shape.pos;
-                self.space.contribute(x, y, shape.contribution);
-            }
-        }
+        // Phase 4: Space decay (A4)
+        self.space.tick();
 
-        // Phase 2: Lifetime decay (A2)
-        for shape in &mut self.shapes {
-            shape.tick();
+        // Phase 5: Report topography changes
+        if let Some(tracker) = &mut self.tracker {
+            tracker.scan(&self.space, &mut self.events);
+        }
+        if !self.events.is_empty() {
+            for observer in &mut self.observers {
+                observer.on_events(self.tick_count, &self.events);
+            }
+            self.events.clear();
         }
 
-        // Phase 3: Remove dead shapes (A5)
-        // Ghost trace prevention: dead shapes can't contribute (A1 ∧ A3)
-        self.shapes.retain(|s| s.is_alive());
-
-        // Phase 4: Space decay (A4)
-        self.space.tick();
+        self.spawns.end_tick();
     }
 
     /// Run multiple ticks.
@@ -96,16 +353,39 @@ impl Substrate {
         self.tick_count
     }
 
+    /// Resume the tick count (snapshot restore).
+    pub(crate) fn set_tick_count(&mut self, tick_count: u64) {
+        self.tick_count = tick_count;
+    }
+
     /// Get number of living shapes.
     pub fn shape_count(&self) -> usize {
         self.shapes.len()
     }
 
+    /// Get payload hosted by a living shape.
+    pub fn payload(&self, id: u64) -> Option<&P> {
+        self.shapes
+            .iter()
+            .find(|s| s.id == id)
+            .and_then(|s| s.payload())
+    }
+
     /// Get reference to space.
-    pub fn space(&self) -> &Space {
+    pub fn space(&self) -> &Space<F> {
         &self.space
     }
 
+    /// Compute field statistics, including spawn acceptance counters.
+    pub fn stats(&self) -> FieldStats {
+        let spawns = self.spawns.total();
+        FieldStats {
+            accepted: spawns.accepted,
+            rejected: spawns.rejected,
+            ..self.space.stats()
+        }
+    }
+
     /// Get density map as flat vector (for visualization).
     pub fn density_map(&self) -> Vec<u32> {
         let (w, h) = self.space.dimensions();
@@ -119,6 +399,82 @@ impl Substrate {
     }
 }
 
+/// Advance shapes by one tick, without decaying the space.
+///
+/// 0. Relationality is evaluated (A0)
+/// 1. Each alive, related shape with payload contributes trace (A3)
+/// 2. Each shape loses one tick of lifetime (A2)
+/// 3. Dead shapes are removed and handed to `on_death` (A5)
+pub(crate) fn advance_shapes<P, F, E>(
+    space: &Space<F>,
+    shapes: &mut Vec<Shape<P>>,
+    relation: Relation,
+    links: &mut Vec<(u64, u64)>,
+    on_death: &mut E,
+) where
+    F: TraceField,
+    E: FnMut(u64, Option<P>),
+{
+    // Phase 0: Relationality (A0)
+    relation.evaluate(shapes, links);
+
+    // Phase 1: Contribution (A3)
+    for shape in shapes.iter() {
+        if shape.can_produce_trace() {
+            space.contribute_shape(shape);
+        }
+    }
+
+    // Phase 2: Lifetime decay (A2)
+    for shape in shapes.iter_mut() {
+        shape.tick();
+    }
+
+    // Phase 3: Remove dead shapes (A5)
+    // Ghost trace prevention: dead shapes can't contribute (A1 ∧ A3)
+    shapes.retain_mut(|s| {
+        if s.is_alive() {
+            return true;
+        }
+        on_death(s.id, s.detach_payload());
+        false
+    });
+    if !links.is_empty() {
+        let alive = |id| shapes.iter().any(|s| s.id == id);
+        links.retain(|&(a, b)| alive(a) && alive(b));
+    }
+}
+
+impl<F: TraceField> Substrate<(), F> {
+    /// Spawn a new shape at position.
+    ///
+    /// Returns the shape ID, or None if position is not habitable.
+    pub fn spawn(
+        &mut self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+    ) -> Option<u64> {
+        self.spawn_with(x, y, lifetime, contribution, ()).ok()
+    }
+
+    /// Spawn a new shape contributing trace in `color`.
+    ///
+    /// Returns the shape ID, or None if position is not habitable.
+    pub fn spawn_colored(
+        &mut self,
+        x: usize,
+        y: usize,
+        lifetime: u32,
+        contribution: u32,
+        color: ServiceColor,
+    ) -> Option<u64> {
+        self.spawn_colored_with(x, y, lifetime, contribution, color, ())
+            .ok()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,4 +560,319 @@ mod tests {
         sub.run(3); // density = 120 > solid (100)
         assert_eq!(sub.space().regime(5, 5), Regime::Solid);
     }
+
+    #[test]
+    fn test_payload_dropped_on_death() {
+        use std::rc::Rc;
+
+        let mut sub = Substrate::new(10, 10, 0, 1000);
+        let job = Rc::new("job");
+
+        let id = sub.spawn_with(1, 1, 2, 1, Rc::clone(&job)).unwrap();
+        assert_eq!(sub.payload(id).map(|p| **p), Some("job"));
+        assert_eq!(Rc::strong_count(&job), 2);
+
+        // Still alive after one tick - payload still hosted
+        sub.tick();
+        assert_eq!(Rc::strong_count(&job), 2);
+
+        // Death = drop
+        sub.tick();
+        assert_eq!(sub.shape_count(), 0);
+        assert_eq!(Rc::strong_count(&job), 1);
+    }
+
+    #[test]
+    fn test_expired_payloads_handed_back() {
+        let mut sub = Substrate::new(10, 10, 0, 1000);
+
+        let short = sub.spawn_with(0, 0, 1, 1, "short").unwrap();
+        sub.spawn_with(0, 1, 5, 1, "long").unwrap();
+
+        let mut expired = Vec::new();
+        sub.tick_with(|id, payload| expired.push((id, payload)));
+
+        assert_eq!(expired, vec![(short, "short")]);
+        assert_eq!(sub.shape_count(), 1);
+    }
+
+    #[test]
+    fn test_isolated_shape_leaves_no_trace() {
+        let mut sub = Substrate::new(10, 10, 0, 1000);
+        sub.set_relation(Relation::Proximity { radius: 1 });
+
+        // A0: payload without relation = no temporal observation
+        sub.spawn(2, 2, 5, 10);
+        sub.run(3);
+        assert_eq!(sub.space().density(2, 2), 0);
+        assert_eq!(sub.shape_count(), 1);
+
+        // A neighbour makes both shapes related
+        sub.spawn(3, 3, 5, 10);
+        sub.tick();
+        assert_eq!(sub.space().density(2, 2), 10);
+        assert_eq!(sub.space().density(3, 3), 10);
+    }
+
+    #[test]
+    fn test_group_and_link_relations() {
+        let mut sub = Substrate::new(10, 10, 0, 1000);
+        sub.set_relation(Relation::Group);
+
+        let a = sub.spawn(0, 0, 10, 10).unwrap();
+        let b = sub.spawn(9, 9, 10, 10).unwrap();
+        assert!(sub.set_group(a, 1));
+        assert!(sub.set_group(b, 1));
+        sub.tick();
+        assert_eq!(sub.space().density(0, 0), 10);
+        assert_eq!(sub.space().density(9, 9), 10);
+
+        sub.set_relation(Relation::Link);
+        sub.tick();
+        assert_eq!(sub.space().density(0, 0), 10, "Unlinked shapes leave no trace");
+
+        assert!(sub.link(a, b));
+        sub.tick();
+        assert_eq!(sub.space().density(0, 0), 20);
+    }
+
+    #[test]
+    fn test_link_dropped_on_death() {
+        let mut sub = Substrate::new(10, 10, 0, 1000);
+        sub.set_relation(Relation::Link);
+
+        let a = sub.spawn(0, 0, 10, 10).unwrap();
+        let b = sub.spawn(5, 5, 1, 10).unwrap();
+        assert!(sub.link(a, b));
+
+        // b dies after its first tick
+        sub.tick();
+        assert_eq!(sub.space().density(0, 0), 10);
+
+        // a is isolated again
+        sub.tick();
+        assert_eq!(sub.space().density(0, 0), 10);
+        assert!(!sub.link(a, b));
+    }
+
+    #[test]
+    fn test_footprint_builds_hill() {
+        let mut sub = Substrate::new(20, 20, 0, 10_000);
+
+        let footprint = Footprint::Gaussian { sigma: 2.0, radius: 4 };
+        sub.spawn_shaped(10, 10, 10, 1000, footprint, ()).unwrap();
+        sub.tick();
+
+        // Total mass conserved, spread over a broad hill
+        let map = sub.density_map();
+        assert_eq!(map.iter().sum::<u32>(), 1000);
+        assert!(map.iter().filter(|&&d| d > 0).count() > 20);
+
+        let peak = sub.space().density(10, 10);
+        assert!(peak > sub.space().density(12, 10));
+        assert!(sub.space().density(12, 10) > sub.space().density(14, 10));
+    }
+
+    #[test]
+    fn test_footprint_radius_clamped_to_grid() {
+        let mut sub = Substrate::new(6, 4, 0, 10_000);
+
+        let far = Footprint::Disk { radius: 1_000_000 };
+        sub.spawn_shaped(0, 0, 10, 240, far, ()).unwrap();
+        sub.tick();
+
+        // Radius 6 from a corner reaches the whole grid
+        let map = sub.density_map();
+        assert!(map.iter().all(|&d| d > 0));
+    }
+
+    #[test]
+    fn test_footprint_habitability_modes() {
+        let mut sub = Substrate::new(20, 20, 0, 100);
+
+        // One saturated needle next to the target center
+        sub.spawn(11, 10, 100, 200);
+        sub.tick();
+
+        let disk = Footprint::Disk { radius: 2 };
+        assert!(sub.spawn_shaped(10, 10, 10, 10, disk, ()).is_err());
+
+        // Mean over 13 cells is well below threshold
+        sub.set_habitability(Habitability::Mean);
+        assert!(sub.spawn_shaped(10, 10, 10, 10, disk, ()).is_ok());
+    }
+
+    #[test]
+    fn test_isotope_lifecycle() {
+        let mut sub = Substrate::with_isotopes(10, 10, 0, 1000);
+
+        sub.spawn_colored(4, 4, 3, 100, ServiceColor::red());
+        sub.spawn_colored(4, 4, 3, 50, ServiceColor::green());
+        sub.run(3);
+
+        // Colored trace accumulated while shapes lived (A3)
+        let grid = sub.space().field();
+        assert_eq!(grid.rgb(4, 4), (300, 150, 0));
+        assert_eq!(grid.dominant_channel(4, 4), Some('R'));
+
+        // Shapes died (A5) - no ghost trace
+        assert_eq!(sub.shape_count(), 0);
+        sub.run(3);
+        assert_eq!(sub.space().density(4, 4), 450);
+    }
+
+    #[test]
+    fn test_isotope_habitability_on_total_density() {
+        let mut sub = Substrate::with_isotopes(10, 10, 0, 100);
+
+        sub.spawn_colored(1, 1, 100, 60, ServiceColor::red());
+        sub.spawn_colored(1, 1, 100, 60, ServiceColor::blue());
+        sub.tick();
+
+        // Neither channel alone exceeds threshold, total does (A1)
+        assert_eq!(sub.space().regime(1, 1), Regime::Solid);
+        assert!(sub
+            .spawn_colored(1, 1, 10, 1, ServiceColor::green())
+            .is_none());
+    }
+
+    #[test]
+    fn test_observer_receives_tick_events() {
+        use std::sync::{Arc, Mutex};
+
+        let mut sub = Substrate::new(10, 10, 0, 100);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        sub.add_observer(move |tick, events: &[Event]| {
+            sink.lock().unwrap().push((tick, events.to_vec()));
+        });
+
+        let id = sub.spawn(5, 5, 1, 120).unwrap();
+        sub.tick();
+        assert!(sub.spawn(5, 5, 1, 10).is_none());
+        sub.tick();
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 2);
+
+        let (tick, events) = &log[0];
+        assert_eq!(*tick, 1);
+        assert!(events.contains(&Event::ShapeDied { id }));
+        assert!(events.contains(&Event::RegimeChanged {
+            x: 5,
+            y: 5,
+            from: Regime::Gas,
+            to: Regime::Solid
+        }));
+        assert!(events.contains(&Event::CellSaturated { x: 5, y: 5 }));
+
+        // Rejection between ticks is reported with the next tick
+        assert_eq!(log[1], (2, vec![Event::SpawnRejected { x: 5, y: 5 }]));
+    }
+
+    #[test]
+    fn test_event_hysteresis_suppresses_flapping() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Decay noise around the threshold: density alternates 105 / 95
+        let mut sub = Substrate::<()>::new(4, 4, 10, 100);
+        sub.space().contribute(1, 1, 95);
+        sub.set_event_hysteresis(3);
+
+        let flips = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&flips);
+        sub.add_observer(move |_, events: &[Event]| {
+            counter.fetch_add(events.len(), Ordering::Relaxed);
+        });
+
+        for i in 0..20 {
+            // Alternate between just above and just below threshold
+            let amount = if i % 2 == 0 { 20 } else { 0 };
+            sub.space().contribute(1, 1, amount);
+            sub.tick();
+        }
+        assert_eq!(flips.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_admission_hysteresis_under_decay() {
+        let thresholds = RegimeThresholds::with_margin(100, 50, 20);
+        let mut sub = Substrate::<()>::try_new(4, 4, 10, thresholds).unwrap();
+        sub.space().contribute(1, 1, 105);
+
+        // Decay noise around the threshold: density alternates 105 / 95
+        for _ in 0..10 {
+            assert!(sub.spawn(1, 1, 1, 0).is_none());
+            sub.tick();
+            assert_eq!(sub.space().density(1, 1), 95);
+            assert!(!sub.space().is_habitable(1, 1));
+            assert!(!sub.space().try_contribute(1, 1, 1, ServiceColor::neutral()));
+            sub.space().contribute(1, 1, 10);
+        }
+
+        // Reopens only at the Solid exit boundary
+        sub.tick();
+        sub.tick();
+        assert_eq!(sub.space().density(1, 1), 85);
+        assert!(sub.spawn(1, 1, 1, 0).is_none());
+        sub.tick();
+        assert_eq!(sub.space().regime(1, 1), Regime::Liquid);
+        assert!(sub.spawn(1, 1, 1, 0).is_some());
+        assert!(sub.space().try_contribute(1, 1, 5, ServiceColor::neutral()));
+    }
+
+    #[test]
+    fn test_rejected_spawn_returns_payload() {
+        let mut sub = Substrate::new(10, 10, 0, 50);
+
+        sub.spawn_with(3, 3, 100, 60, "first").unwrap();
+        sub.tick();
+
+        assert_eq!(sub.spawn_with(3, 3, 100, 10, "second"), Err("second"));
+    }
+
+    #[test]
+    fn test_stats_count_spawns() {
+        let mut sub = Substrate::new(10, 10, 0, 50);
+        sub.spawn(3, 3, 100, 60).unwrap();
+        sub.tick();
+
+        assert!(sub.spawn(3, 3, 100, 10).is_none());
+        assert!(sub.spawn(4, 4, 100, 10).is_some());
+
+        let stats = sub.stats();
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.rejected, 1);
+        assert_eq!(stats.total_mass, 60);
+        assert!((stats.rejection_rate() - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_spawn_accounting() {
+        let mut sub = Substrate::new(8, 8, 0, 50);
+        sub.track_spawn_regions(4);
+        sub.track_spawn_colors();
+        sub.track_rejections(255);
+
+        let red = ServiceColor::red();
+        sub.spawn_colored(1, 1, 100, 60, red).unwrap();
+        sub.tick();
+        assert!(sub.spawn_colored(1, 1, 100, 10, red).is_none());
+        assert!(sub.spawn(6, 6, 100, 10).is_some());
+
+        let stats = sub.spawn_stats();
+        assert_eq!(stats.last_tick().accepted, 1);
+        assert_eq!(stats.current().attempted(), 2);
+        assert_eq!(stats.total().rejected, 1);
+        assert_eq!(stats.region_at(0, 0).unwrap().rejected, 1);
+        assert_eq!(stats.region_at(7, 7).unwrap().accepted, 1);
+        assert_eq!(stats.color(red).unwrap().attempted(), 2);
+        assert_eq!(stats.heatmap().unwrap().intensity(1, 1), 255);
+
+        // Flash lasts one tick with a full fade step
+        sub.tick();
+        assert_eq!(sub.spawn_stats().heatmap().unwrap().intensity(1, 1), 0);
+        assert_eq!(sub.spawn_stats().last_tick().rejected, 1);
+    }
 }
@@ -0,0 +1,143 @@
+//! Topology - the shape of the lattice and what happens at its edges
+//!
+//! `V` is a discrete approximation of a region of ℝ² (a bounded plane) or
+//! of a torus. The topology decides where diffusion leaks and where
+//! footprints land; the boundary decides whether trace leaving a bounded
+//! plane is kept or lost.
+
+use crate::footprint;
+
+/// Global shape of the lattice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Topology {
+    /// Bounded plane: edges are edges (see [`Boundary`])
+    #[default]
+    Plane,
+    /// Torus: opposite edges are joined, there is no boundary
+    Torus,
+}
+
+/// Edge behavior of a bounded plane (ignored on a torus).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Boundary {
+    /// Edge cells hold their trace: they receive diffusion but do not leak
+    #[default]
+    Closed,
+    /// Edge cells leak like any other; trace leaving the plane is lost
+    Open,
+}
+
+impl Topology {
+    /// Offset `(x, y)` by `(dx, dy)` on a `width × height` lattice.
+    ///
+    /// None if the result leaves a bounded plane.
+    #[inline]
+    pub(crate) fn offset(
+        self,
+        (width, height): (usize, usize),
+        x: usize,
+        y: usize,
+        dx: isize,
+        dy: isize,
+    ) -> Option<(usize, usize)> {
+        match self {
+            Topology::Plane => {
+                footprint::offset(x, y, dx, dy).filter(|&(cx, cy)| cx < width && cy < height)
+            }
+            Topology::Torus => {
+                if width == 0 || height == 0 {
+                    return None;
+                }
+                let wrap =
+                    |v: usize, d: isize, n: usize| (v as isize + d).rem_euclid(n as isize) as usize;
+                Some((wrap(x, dx, width), wrap(y, dy, height)))
+            }
+        }
+    }
+}
+
+/// Diffusion geometry of a `width × height` grid.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Lattice {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) topology: Topology,
+    pub(crate) boundary: Boundary,
+}
+
+impl Lattice {
+    /// Cells that leak during diffusion, row-major.
+    pub(crate) fn sources(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (w, h) = (self.width, self.height);
+        let closed = self.topology == Topology::Plane && self.boundary == Boundary::Closed;
+        let (xs, ys) = if closed {
+            (1..w.saturating_sub(1), 1..h.saturating_sub(1))
+        } else {
+            (0..w, 0..h)
+        };
+        ys.flat_map(move |y| xs.clone().map(move |x| (x, y)))
+    }
+
+    /// Indices of the 4-connected neighbors (up, down, left, right).
+    ///
+    /// None where the neighbor lies off an open plane (trace is lost).
+    pub(crate) fn neighbors(&self, x: usize, y: usize) -> [Option<usize>; 4] {
+        let dims = (self.width, self.height);
+        [(0, -1), (0, 1), (-1, 0), (1, 0)].map(|(dx, dy)| {
+            self.topology
+                .offset(dims, x, y, dx, dy)
+                .map(|(nx, ny)| ny * self.width + nx)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lattice(topology: Topology, boundary: Boundary) -> Lattice {
+        Lattice {
+            width: 4,
+            height: 3,
+            topology,
+            boundary,
+        }
+    }
+
+    #[test]
+    fn test_closed_plane_leaks_from_interior_only() {
+        let sources: Vec<_> = lattice(Topology::Plane, Boundary::Closed)
+            .sources()
+            .collect();
+        assert_eq!(sources, vec![(1, 1), (2, 1)]);
+
+        // Degenerate grids have no interior
+        let thin = Lattice {
+            width: 1,
+            height: 1,
+            topology: Topology::Plane,
+            boundary: Boundary::Closed,
+        };
+        assert_eq!(thin.sources().count(), 0);
+    }
+
+    #[test]
+    fn test_open_plane_loses_edge_neighbors() {
+        let open = lattice(Topology::Plane, Boundary::Open);
+        assert_eq!(open.sources().count(), 12);
+        assert_eq!(open.neighbors(0, 0), [None, Some(4), None, Some(1)]);
+    }
+
+    #[test]
+    fn test_torus_wraps() {
+        let torus = lattice(Topology::Torus, Boundary::Closed);
+        assert_eq!(torus.sources().count(), 12);
+        assert_eq!(torus.neighbors(0, 0), [Some(8), Some(4), Some(3), Some(1)]);
+        assert_eq!(Topology::Torus.offset((4, 3), 3, 2, 2, 2), Some((1, 1)));
+        assert_eq!(Topology::Plane.offset((4, 3), 3, 2, 1, 0), None);
+    }
+}
@@ -0,0 +1,445 @@
+//! Waiting - admission that waits for habitability
+//!
+//! [`Space::try_contribute`] rejects a saturated cell. A [`WaitingRoom`]
+//! lets callers wait instead: [`WaitingRoom::acquire`] returns a future
+//! that resolves once a tick's decay has made the cell (or one of its
+//! fallback cells) habitable again, or fails at its deadline.
+//!
+//! - No busy polling: waiters are parked and only woken when the ticker
+//!   grants them a cell or their deadline passes.
+//! - Fairness: after each tick, waiters are served in arrival order, and a
+//!   newcomer does not jump ahead of waiters queued on the same cells.
+//! - Cancellation: dropping the future leaves the queue.
+//!
+//! Deadlines are tick counts: ticks are the only clock of the substrate
+//! (drive them with a [`TickDriver`](crate::TickDriver)).
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use crate::driver::Tick;
+use crate::field::TraceField;
+use crate::grid::DensityGrid;
+use crate::isotope::ServiceColor;
+use crate::space::Space;
+
+/// The wait for a habitable cell ended at its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcquireError {
+    /// Tick at which the wait gave up
+    pub deadline: u64,
+}
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no habitable cell by tick {}", self.deadline)
+    }
+}
+
+impl std::error::Error for AcquireError {}
+
+/// What an acquisition asks for.
+struct Request {
+    /// Preferred cell first, then fallbacks in order
+    cells: Vec<(usize, usize)>,
+    amount: u32,
+    color: ServiceColor,
+    deadline: u64,
+}
+
+enum WaitState {
+    Waiting(Waker),
+    Granted((usize, usize)),
+    TimedOut,
+}
+
+/// A parked acquisition.
+struct Waiter {
+    request: Request,
+    state: Mutex<WaitState>,
+}
+
+impl Waiter {
+    fn state(&self) -> MutexGuard<'_, WaitState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Resolve and wake the waiting task.
+    fn resolve(&self, outcome: WaitState) {
+        if let WaitState::Waiting(waker) = std::mem::replace(&mut *self.state(), outcome) {
+            waker.wake();
+        }
+    }
+}
+
+/// A space whose saturated cells can be waited on.
+///
+/// Ticking the room decays the space and then serves parked acquisitions.
+pub struct WaitingRoom<F = DensityGrid> {
+    space: Space<F>,
+    /// Parked acquisitions in arrival order
+    queue: Mutex<VecDeque<Arc<Waiter>>>,
+    tick_count: AtomicU64,
+}
+
+impl<F: TraceField> WaitingRoom<F> {
+    /// Create a waiting room over a space.
+    pub fn new(space: Space<F>) -> Self {
+        Self {
+            space,
+            queue: Mutex::new(VecDeque::new()),
+            tick_count: AtomicU64::new(0),
+        }
+    }
+
+    /// Wait until `amount` can be contributed at `(x, y)`.
+    ///
+    /// Resolves to the cell that admitted the contribution, or fails once
+    /// tick `deadline` has passed without a grant. A deadline at or before
+    /// the current tick makes a single immediate attempt.
+    pub fn acquire(&self, x: usize, y: usize, amount: u32, deadline: u64) -> Acquire<'_, F> {
+        Acquire {
+            room: self,
+            request: Some(Request {
+                cells: vec![(x, y)],
+                amount,
+                color: ServiceColor::neutral(),
+                deadline,
+            }),
+            waiter: None,
+        }
+    }
+
+    /// Advance by one tick: decay, then serve waiters in arrival order.
+    pub fn tick(&self) {
+        let mut queue = self.queue();
+        self.space.tick();
+        let now = self.tick_count.fetch_add(1, Ordering::Relaxed) + 1;
+
+        queue.retain(|waiter| {
+            if let Some(cell) = self.admit(&waiter.request) {
+                waiter.resolve(WaitState::Granted(cell));
+                false
+            } else if now >= waiter.request.deadline {
+                waiter.resolve(WaitState::TimedOut);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Ticks run so far.
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count.load(Ordering::Relaxed)
+    }
+
+    /// Number of parked acquisitions.
+    pub fn waiting(&self) -> usize {
+        self.queue().len()
+    }
+
+    /// Get the space.
+    pub fn space(&self) -> &Space<F> {
+        &self.space
+    }
+
+    /// First candidate cell that admits the contribution.
+    fn admit(&self, request: &Request) -> Option<(usize, usize)> {
+        request.cells.iter().copied().find(|&(x, y)| {
+            self.space
+                .try_contribute(x, y, request.amount, request.color)
+        })
+    }
+
+    fn queue(&self) -> MutexGuard<'_, VecDeque<Arc<Waiter>>> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<F: TraceField> Tick for WaitingRoom<F> {
+    fn tick(&self) {
+        WaitingRoom::tick(self);
+    }
+}
+
+/// Future returned by [`WaitingRoom::acquire`].
+///
+/// Options can be added before the first poll.
+pub struct Acquire<'a, F> {
+    room: &'a WaitingRoom<F>,
+    /// Set until the first poll
+    request: Option<Request>,
+    /// Set while parked
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl<F> Acquire<'_, F> {
+    /// Color of the contributed trace (default neutral).
+    pub fn color(mut self, color: ServiceColor) -> Self {
+        if let Some(request) = &mut self.request {
+            request.color = color;
+        }
+        self
+    }
+
+    /// Also accept any of `cells`, tried in order after the preferred cell.
+    pub fn fallback(mut self, cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        if let Some(request) = &mut self.request {
+            request.cells.extend(cells);
+        }
+        self
+    }
+}
+
+impl<F: TraceField> Future for Acquire<'_, F> {
+    type Output = Result<(usize, usize), AcquireError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Some(waiter) = &this.waiter {
+            let mut state = waiter.state();
+            return match &mut *state {
+                WaitState::Waiting(waker) => {
+                    waker.clone_from(cx.waker());
+                    Poll::Pending
+                }
+                WaitState::Granted(cell) => {
+                    let cell = *cell;
+                    drop(state);
+                    this.waiter = None;
+                    Poll::Ready(Ok(cell))
+                }
+                WaitState::TimedOut => {
+                    let deadline = waiter.request.deadline;
+                    drop(state);
+                    this.waiter = None;
+                    Poll::Ready(Err(AcquireError { deadline }))
+                }
+            };
+        }
+
+        let request = this
+            .request
+            .take()
+            .expect("Acquire polled after completion");
+        let room = this.room;
+        let mut queue = room.queue();
+
+        // Do not overtake waiters queued on any of the same cells
+        let contended = queue
+            .iter()
+            .any(|w| w.request.cells.iter().any(|c| request.cells.contains(c)));
+        if !contended {
+            if let Some(cell) = room.admit(&request) {
+                return Poll::Ready(Ok(cell));
+            }
+        }
+        if request.deadline <= room.tick_count() {
+            return Poll::Ready(Err(AcquireError {
+                deadline: request.deadline,
+            }));
+        }
+
+        let waiter = Arc::new(Waiter {
+            request,
+            state: Mutex::new(WaitState::Waiting(cx.waker().clone())),
+        });
+        queue.push_back(Arc::clone(&waiter));
+        this.waiter = Some(waiter);
+        Poll::Pending
+    }
+}
+
+impl<F> Drop for Acquire<'_, F> {
+    fn drop(&mut self) {
+        // A grant that raced with cancellation keeps its trace, like a
+        // request admitted and then abandoned.
+        if let Some(waiter) = self.waiter.take() {
+            self.room
+                .queue
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .retain(|w| !Arc::ptr_eq(w, &waiter));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::task::Wake;
+
+    /// Waker counting its wake-ups.
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    impl Wake for Counter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn poll<Fut: Future + Unpin>(future: &mut Fut, counter: &Arc<Counter>) -> Poll<Fut::Output> {
+        let waker = Waker::from(Arc::clone(counter));
+        Pin::new(future).poll(&mut Context::from_waker(&waker))
+    }
+
+    fn wakes(counter: &Counter) -> usize {
+        counter.0.load(Ordering::Relaxed)
+    }
+
+    /// Room with a 1x2 space: threshold 100, decay 50 per tick.
+    fn room() -> WaitingRoom {
+        WaitingRoom::new(Space::new(2, 1, 50, 100))
+    }
+
+    #[test]
+    fn test_waits_for_decay() {
+        let room = room();
+        let counter = Arc::new(Counter::default());
+
+        let mut first = room.acquire(0, 0, 100, 10);
+        assert_eq!(poll(&mut first, &counter), Poll::Ready(Ok((0, 0))));
+
+        let mut second = room.acquire(0, 0, 100, 10);
+        assert!(poll(&mut second, &counter).is_pending());
+        assert_eq!(room.waiting(), 1);
+
+        // 100 → 50: habitable again, granted by the tick itself
+        room.tick();
+        assert_eq!(wakes(&counter), 1);
+        assert_eq!(poll(&mut second, &counter), Poll::Ready(Ok((0, 0))));
+        assert_eq!(room.space().density(0, 0), 150);
+        assert_eq!(room.waiting(), 0);
+    }
+
+    #[test]
+    fn test_fifo_and_no_overtaking() {
+        let room = room();
+        let counter = Arc::new(Counter::default());
+        room.space().contribute(0, 0, 150);
+
+        let mut a = room.acquire(0, 0, 60, 10);
+        let mut b = room.acquire(0, 0, 60, 10);
+        assert!(poll(&mut a, &counter).is_pending());
+        assert!(poll(&mut b, &counter).is_pending());
+
+        // 150 → 100: still saturated, nobody is woken
+        room.tick();
+        assert_eq!(wakes(&counter), 0);
+
+        // 100 → 50: `a` arrived first and takes the cell (→ 110)
+        room.tick();
+        assert!(poll(&mut a, &counter).is_ready());
+        assert!(poll(&mut b, &counter).is_pending());
+
+        // A newcomer queues behind `b`
+        let mut c = room.acquire(0, 0, 60, 10);
+        assert!(poll(&mut c, &counter).is_pending());
+
+        // 110 → 60: `b` first (→ 120), `c` waits for the next tick
+        room.tick();
+        assert!(poll(&mut b, &counter).is_ready());
+        assert!(poll(&mut c, &counter).is_pending());
+        room.tick();
+        assert!(poll(&mut c, &counter).is_ready());
+    }
+
+    #[test]
+    fn test_fallback_region() {
+        let room = room();
+        let counter = Arc::new(Counter::default());
+        room.space().contribute(0, 0, 500);
+
+        let mut acquire = room.acquire(0, 0, 30, 0).fallback([(1, 0)]);
+        assert_eq!(poll(&mut acquire, &counter), Poll::Ready(Ok((1, 0))));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let room = room();
+        let counter = Arc::new(Counter::default());
+        room.space().contribute(0, 0, 500);
+
+        // Deadline already reached: single attempt
+        let mut now = room.acquire(0, 0, 10, 0);
+        assert_eq!(
+            poll(&mut now, &counter),
+            Poll::Ready(Err(AcquireError { deadline: 0 }))
+        );
+
+        let mut later = room.acquire(0, 0, 10, 2);
+        assert!(poll(&mut later, &counter).is_pending());
+        room.tick();
+        assert_eq!(wakes(&counter), 0);
+        room.tick();
+        assert_eq!(wakes(&counter), 1);
+        assert!(matches!(poll(&mut later, &counter), Poll::Ready(Err(_))));
+    }
+
+    #[test]
+    fn test_cancellation_leaves_queue() {
+        let room = room();
+        let counter = Arc::new(Counter::default());
+        room.space().contribute(0, 0, 120);
+
+        let mut dropped = room.acquire(0, 0, 60, 10);
+        let mut kept = room.acquire(0, 0, 60, 10);
+        assert!(poll(&mut dropped, &counter).is_pending());
+        assert!(poll(&mut kept, &counter).is_pending());
+        drop(dropped);
+        assert_eq!(room.waiting(), 1);
+
+        // 120 → 70: the cell goes to the remaining waiter
+        room.tick();
+        assert!(poll(&mut kept, &counter).is_ready());
+        assert_eq!(room.space().density(0, 0), 130);
+    }
+
+    #[test]
+    fn test_parked_thread_is_woken_by_ticker() {
+        struct Unpark(std::thread::Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let room = Arc::new(room());
+        room.space().contribute(0, 0, 100);
+
+        let ticker = {
+            let room = Arc::clone(&room);
+            std::thread::spawn(move || {
+                while room.waiting() == 0 {
+                    std::thread::yield_now();
+                }
+                room.tick();
+            })
+        };
+
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut acquire = room.acquire(0, 0, 10, 5);
+        let mut polls = 0;
+        let cell = loop {
+            polls += 1;
+            if let Poll::Ready(out) = Pin::new(&mut acquire).poll(&mut cx) {
+                break out.unwrap();
+            }
+            std::thread::park();
+        };
+        ticker.join().unwrap();
+
+        assert_eq!(cell, (0, 0));
+        assert!(polls <= 3, "polled {} times", polls);
+    }
+}
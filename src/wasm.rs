@@ -0,0 +1,148 @@
+//! Wasm - browser bindings for live topography (feature `wasm`)
+//!
+//! Exports an `IsotopeGrid` class to JavaScript whose `frame()` returns the
+//! field as RGBA bytes in the `tes-viz` palette, ready for
+//! `new ImageData(frame, grid.width, grid.height)`. Contributions are
+//! admission-checked (A1) and rejections flash magenta in the next frames.
+//!
+//! Nothing here needs threads or a clock: the page drives `tick()` (e.g.
+//! from `requestAnimationFrame`). Build and test:
+//!
+//! ```text
+//! cargo rustc --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/tes.wasm
+//! cargo test --target wasm32-unknown-unknown --features wasm --lib   # wasm-bindgen-test-runner, node
+//! ```
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+
+use crate::accounting::RejectionHeatmap;
+use crate::isotope::{IsotopeGrid, ServiceColor};
+use crate::palette;
+
+/// Rejection flashes fade out over this many ticks.
+const FLASH_TICKS: u8 = 8;
+
+/// An [`IsotopeGrid`] with the rejection flashes of its viewer.
+#[wasm_bindgen(js_name = IsotopeGrid)]
+pub struct WasmIsotopeGrid {
+    grid: IsotopeGrid,
+    threshold: u32,
+    flashes: RejectionHeatmap,
+}
+
+#[wasm_bindgen(js_class = IsotopeGrid)]
+impl WasmIsotopeGrid {
+    /// Create a grid admitting trace below `threshold`; the liquid boundary
+    /// is `threshold / 2`. Throws on invalid parameters.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        width: usize,
+        height: usize,
+        decay_rate: u32,
+        threshold: u32,
+    ) -> Result<WasmIsotopeGrid, JsError> {
+        let grid = IsotopeGrid::try_new(width, height, decay_rate, threshold, threshold / 2)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Self {
+            grid,
+            threshold,
+            flashes: RejectionHeatmap::new(width, height, u8::MAX.div_ceil(FLASH_TICKS)),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.grid.dimensions().0
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.grid.dimensions().1
+    }
+
+    /// Leave trace in the color of `service` if the cell is habitable.
+    /// Returns false and flashes the cell otherwise.
+    pub fn contribute(&mut self, x: usize, y: usize, amount: u32, service: &str) -> bool {
+        self.admit(x, y, amount, ServiceColor::from_name(service))
+    }
+
+    /// [`contribute`](Self::contribute) with an explicit color (0-1000 per
+    /// channel).
+    #[wasm_bindgen(js_name = contributeRgb)]
+    pub fn contribute_rgb(
+        &mut self,
+        x: usize,
+        y: usize,
+        amount: u32,
+        r: u32,
+        g: u32,
+        b: u32,
+    ) -> bool {
+        self.admit(x, y, amount, ServiceColor { r, g, b })
+    }
+
+    pub fn density(&self, x: usize, y: usize) -> u32 {
+        self.grid.density(x, y)
+    }
+
+    /// Spread trace to the neighbors once.
+    pub fn diffuse(&self) {
+        self.grid.diffuse();
+    }
+
+    /// Apply one tick of decay (A3) and fade rejection flashes.
+    pub fn tick(&mut self) {
+        self.grid.apply_decay();
+        self.flashes.fade();
+    }
+
+    /// Row-major RGBA bytes (`width * height * 4`).
+    pub fn frame(&self) -> Clamped<Vec<u8>> {
+        let mut out = Vec::new();
+        palette::frame(&self.grid, self.threshold, Some(&self.flashes), &mut out);
+        Clamped(out)
+    }
+}
+
+impl WasmIsotopeGrid {
+    fn admit(&mut self, x: usize, y: usize, amount: u32, color: ServiceColor) -> bool {
+        let admitted = self
+            .grid
+            .try_contribute(x, y, amount, color, self.threshold);
+        if !admitted {
+            self.flashes.record(x, y);
+        }
+        admitted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn test_frame_shows_walls_and_flashes() {
+        let mut grid = WasmIsotopeGrid::new(4, 2, 10, 1000).unwrap();
+        assert_eq!((grid.width(), grid.height()), (4, 2));
+
+        assert!(grid.contribute_rgb(1, 0, 1000, 1000, 0, 0));
+        assert!(!grid.contribute_rgb(1, 0, 10, 1000, 0, 0));
+        assert!(grid.contribute(3, 1, 400, "Auth"));
+
+        let frame = grid.frame().0;
+        assert_eq!(frame.len(), 4 * 2 * 4);
+        assert_eq!(&frame[4..8], &palette::FLASH);
+
+        // Flash fades over FLASH_TICKS; decay reopens the wall below it
+        for _ in 0..FLASH_TICKS {
+            grid.tick();
+        }
+        assert_eq!(grid.density(1, 0), 920);
+        assert_eq!(&grid.frame().0[4..8], &[234, 0, 0, 255]);
+        assert!(grid.contribute_rgb(1, 0, 10, 1000, 0, 0));
+    }
+}